@@ -0,0 +1,41 @@
+use azure_tts::{EnUsVoices, Style, VoiceSegment};
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+
+fn write_bytes_to_file(data: &[u8], path: &str) {
+    let mut file = File::create(path).unwrap();
+    file.write_all(data).unwrap();
+    file.flush().unwrap();
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().collect();
+    let subscription_key = args
+        .get(1)
+        .expect("Please provide subscription key as argument");
+    let mut client = azure_tts::VoiceService::new(subscription_key, azure_tts::Region::uksouth);
+    let dialogue = vec![
+        (
+            EnUsVoices::JennyNeural.to_voice_settings(),
+            vec![VoiceSegment::plain("Did you hear the news?")],
+        ),
+        (
+            EnUsVoices::GuyNeural.to_voice_settings(),
+            vec![VoiceSegment::with_expression(
+                "I did! It's wonderful.",
+                Style::Cheerful,
+            )],
+        ),
+    ];
+    let res = client
+        .synthesize_voices(
+            dialogue,
+            azure_tts::AudioFormat::Audio48khz192kbitrateMonoMp3,
+        )
+        .await
+        .unwrap();
+    println!("Response length:\n{}", res.len());
+    write_bytes_to_file(&res, "output.mp3");
+}
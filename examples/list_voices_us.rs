@@ -7,13 +7,19 @@ async fn main() {
         .get(1)
         .expect("Please provide subscription key as argument");
     let mut client = azure_tts::VoiceService::new(subscription_key, azure_tts::Region::uksouth);
-    let voices = client.list_voices().await.unwrap();
-    for voice in voices {
-        if voice.locale == "en-US" {
-            println!(
-                "short_name: {}\ndisplay_name: {}\nlocale: {}\ngender: {:?}\n",
-                voice.short_name, voice.display_name, voice.locale, voice.gender
-            );
-        }
+    let catalog = client.voice_catalog().await.unwrap();
+    let english: azure_tts::Locale = "en-US".parse().unwrap();
+    // with_locale also matches voices speaking en-US as a secondary locale
+    for voice in catalog
+        .query()
+        .with_locale(english.clone())
+        .voices()
+        .into_iter()
+        .filter(|voice| voice.locale == english)
+    {
+        println!(
+            "short_name: {}\ndisplay_name: {}\nlocale: {}\ngender: {:?}\n",
+            voice.short_name, voice.display_name, voice.locale, voice.gender
+        );
     }
 }
@@ -0,0 +1,458 @@
+/// Joining audio synthesized in multiple requests into a single stream.
+use crate::{AudioFormat, Result, TtsError};
+
+/// Container of an [`AudioFormat`], decides how parts are joined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Raw,
+    Riff,
+    Mp3,
+    Ogg,
+    Webm,
+}
+
+fn container(audio_format: AudioFormat) -> Container {
+    match audio_format {
+        AudioFormat::Audio16khz128kbitrateMonoMp3
+        | AudioFormat::Audio16khz32kbitrateMonoMp3
+        | AudioFormat::Audio16khz64kbitrateMonoMp3
+        | AudioFormat::Audio24khz160kbitrateMonoMp3
+        | AudioFormat::Audio24khz48kbitrateMonoMp3
+        | AudioFormat::Audio24khz96kbitrateMonoMp3
+        | AudioFormat::Audio48khz192kbitrateMonoMp3
+        | AudioFormat::Audio48khz96kbitrateMonoMp3 => Container::Mp3,
+        AudioFormat::Ogg16khz16bitMonoOpus
+        | AudioFormat::Ogg24khz16bitMonoOpus
+        | AudioFormat::Ogg48khz16bitMonoOpus => Container::Ogg,
+        AudioFormat::Raw16khz16bitMonoPcm
+        | AudioFormat::Raw16khz16bitMonoTruesilk
+        | AudioFormat::Raw24khz16bitMonoPcm
+        | AudioFormat::Raw24khz16bitMonoTruesilk
+        | AudioFormat::Raw48khz16bitMonoPcm
+        | AudioFormat::Raw8khz8bitMonoAlaw
+        | AudioFormat::Raw8khz8bitMonoMulct => Container::Raw,
+        AudioFormat::Riff16khz16bitMonoPcm
+        | AudioFormat::Riff24khz16bitMonoPcm
+        | AudioFormat::Riff48khz16bitMonoPcm
+        | AudioFormat::Riff8khz8bitMonoAlaw
+        | AudioFormat::Riff8khz8bitMonoMulaw => Container::Riff,
+        AudioFormat::Webm16khz16bitMonoOpus | AudioFormat::Webm24khz16bitMonoOpus => {
+            Container::Webm
+        }
+    }
+}
+
+/// Whether parts in `audio_format` can be joined by [`concatenate_audio`]
+pub fn supports_concatenation(audio_format: AudioFormat) -> bool {
+    container(audio_format) != Container::Webm
+}
+
+/// Join audio parts in order into a single playable stream
+///
+/// Riff headers are rewritten to cover all samples, ID3 tags of later mp3 parts and the
+/// Xing/LAME frames of all mp3 parts are dropped, and ogg parts are merged into the logical
+/// stream of the first part.
+pub fn concatenate_audio(parts: Vec<Vec<u8>>, audio_format: AudioFormat) -> Result<Vec<u8>> {
+    match container(audio_format) {
+        Container::Raw => Ok(parts.concat()),
+        Container::Mp3 => Ok(concatenate_mp3(parts)),
+        Container::Riff => concatenate_riff(parts),
+        Container::Ogg => concatenate_ogg(parts),
+        Container::Webm => Err(TtsError::InvalidValue(format!(
+            "can't concatenate {} audio",
+            audio_format.as_string()
+        ))),
+    }
+}
+
+fn concatenate_mp3(parts: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut audio = vec![];
+    for (index, part) in parts.iter().enumerate() {
+        let id3 = id3_length(part).min(part.len());
+        if index == 0 {
+            audio.extend_from_slice(&part[..id3]);
+        }
+        // the info frame of a part would report the duration of that part only
+        let frames = &part[id3..];
+        audio.extend_from_slice(&frames[info_frame_length(frames)..]);
+    }
+    audio
+}
+
+/// Layer III bitrates in kbit/s by bitrate index, for MPEG 1 and for MPEG 2 and 2.5
+const MP3_BITRATES_V1: [usize; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const MP3_BITRATES_V2: [usize; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+/// Length of a leading Xing, Info or VBRI frame of layer III audio
+fn info_frame_length(data: &[u8]) -> usize {
+    if data.len() < 4 || data[0] != 0xff || data[1] & 0xe0 != 0xe0 || data[1] & 0x06 != 0x02 {
+        return 0;
+    }
+    let version = (data[1] >> 3) & 0x03;
+    let bitrate_index = (data[2] >> 4) as usize;
+    let sample_rate_index = ((data[2] >> 2) & 0x03) as usize;
+    if version == 1 || bitrate_index == 0 || bitrate_index == 15 || sample_rate_index == 3 {
+        return 0;
+    }
+    let mpeg1 = version == 3;
+    let (bitrate, sample_rate, size_factor) = if mpeg1 {
+        let sample_rate = [44100, 48000, 32000][sample_rate_index];
+        (MP3_BITRATES_V1[bitrate_index], sample_rate, 144)
+    } else {
+        let sample_rate = [22050, 24000, 16000][sample_rate_index] >> (version == 0) as usize;
+        (MP3_BITRATES_V2[bitrate_index], sample_rate, 72)
+    };
+    let padding = ((data[2] >> 1) & 0x01) as usize;
+    let length = size_factor * bitrate * 1000 / sample_rate + padding;
+    let crc = if data[1] & 0x01 == 0 { 2 } else { 0 };
+    let mono = data[3] >> 6 == 3;
+    let side_info = match (mpeg1, mono) {
+        (true, true) => 17,
+        (true, false) => 32,
+        (false, true) => 9,
+        (false, false) => 17,
+    };
+    let xing = 4 + crc + side_info;
+    let is_info = matches!(data.get(xing..xing + 4), Some(b"Xing") | Some(b"Info"))
+        || data.get(36..40) == Some(b"VBRI");
+    if is_info && length <= data.len() {
+        length
+    } else {
+        0
+    }
+}
+
+/// Length of a leading ID3v2 tag
+fn id3_length(data: &[u8]) -> usize {
+    if data.len() < 10 || &data[..3] != b"ID3" {
+        return 0;
+    }
+    // synchsafe integer, 7 bits per byte
+    let size = data[6..10]
+        .iter()
+        .fold(0, |size, byte| (size << 7) | (*byte as usize & 0x7f));
+    let footer = if data[5] & 0x10 != 0 { 10 } else { 0 };
+    10 + size + footer
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Header up to the data chunk and the sample data of a riff file
+fn split_riff(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    let invalid = || TtsError::InvalidValue("invalid riff audio".to_owned());
+    if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(invalid());
+    }
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let size = read_u32_le(data, offset + 4).ok_or_else(invalid)? as usize;
+        let body = offset + 8;
+        if &data[offset..offset + 4] == b"data" {
+            // streamed responses may not know the final size
+            let end = body.saturating_add(size).min(data.len());
+            return Ok((&data[..body], &data[body..end]));
+        }
+        // chunks are padded to an even size
+        offset = body + size + size % 2;
+    }
+    Err(invalid())
+}
+
+fn concatenate_riff(parts: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    let first = match parts.first() {
+        Some(first) => first,
+        None => return Ok(vec![]),
+    };
+    let (header, _) = split_riff(first)?;
+    let mut samples = vec![];
+    for part in &parts {
+        samples.extend_from_slice(split_riff(part)?.1);
+    }
+    let mut audio = header.to_vec();
+    let header_length = audio.len();
+    audio[header_length - 4..].copy_from_slice(&(samples.len() as u32).to_le_bytes());
+    audio.extend_from_slice(&samples);
+    if samples.len() % 2 == 1 {
+        audio.push(0);
+    }
+    let riff_size = (audio.len() - 8) as u32;
+    audio[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(audio)
+}
+
+const OGG_HEADER_LENGTH: usize = 27;
+const OGG_END_OF_STREAM: u8 = 0x04;
+const OGG_NO_GRANULE: u64 = u64::MAX;
+
+struct OggPage<'a> {
+    header_type: u8,
+    granule: u64,
+    serial: u32,
+    sequence: u32,
+    segment_table: &'a [u8],
+    body: &'a [u8],
+}
+
+fn ogg_pages(data: &[u8]) -> Result<Vec<OggPage<'_>>> {
+    let invalid = || TtsError::InvalidValue("invalid ogg audio".to_owned());
+    let mut pages = vec![];
+    let mut offset = 0;
+    while offset < data.len() {
+        let header = data
+            .get(offset..offset + OGG_HEADER_LENGTH)
+            .filter(|header| &header[..4] == b"OggS")
+            .ok_or_else(invalid)?;
+        let segments = header[26] as usize;
+        let segment_table = data
+            .get(offset + OGG_HEADER_LENGTH..offset + OGG_HEADER_LENGTH + segments)
+            .ok_or_else(invalid)?;
+        let body_start = offset + OGG_HEADER_LENGTH + segments;
+        let body_length: usize = segment_table.iter().map(|lacing| *lacing as usize).sum();
+        let body = data
+            .get(body_start..body_start + body_length)
+            .ok_or_else(invalid)?;
+        let mut granule = [0; 8];
+        granule.copy_from_slice(&header[6..14]);
+        pages.push(OggPage {
+            header_type: header[5],
+            granule: u64::from_le_bytes(granule),
+            serial: read_u32_le(header, 14).ok_or_else(invalid)?,
+            sequence: read_u32_le(header, 18).ok_or_else(invalid)?,
+            segment_table,
+            body,
+        });
+        offset = body_start + body_length;
+    }
+    Ok(pages)
+}
+
+impl OggPage<'_> {
+    fn write(&self, output: &mut Vec<u8>) {
+        let start = output.len();
+        output.extend_from_slice(b"OggS");
+        output.push(0);
+        output.push(self.header_type);
+        output.extend_from_slice(&self.granule.to_le_bytes());
+        output.extend_from_slice(&self.serial.to_le_bytes());
+        output.extend_from_slice(&self.sequence.to_le_bytes());
+        output.extend_from_slice(&[0; 4]);
+        output.push(self.segment_table.len() as u8);
+        output.extend_from_slice(self.segment_table);
+        output.extend_from_slice(self.body);
+        let crc = ogg_crc(&output[start..]);
+        output[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+    }
+}
+
+/// Crc used by ogg pages, polynomial 0x04c11db7 without reflection
+fn ogg_crc(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0;
+    for byte in data {
+        crc ^= (*byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Pre-skip of an opus identification header, the decoder priming samples at 48kHz
+fn opus_pre_skip(page: &OggPage) -> u64 {
+    match page.body.get(..12) {
+        Some(header) if header.starts_with(b"OpusHead") => {
+            u16::from_le_bytes([header[10], header[11]]) as u64
+        }
+        _ => 0,
+    }
+}
+
+fn concatenate_ogg(parts: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    let mut audio = vec![];
+    let mut serial = None;
+    let mut sequence = 0;
+    let mut granule_offset = 0;
+    let part_count = parts.len();
+    for (part_index, part) in parts.iter().enumerate() {
+        let pages = ogg_pages(part)?;
+        // identification and comment headers, the comment header may span several pages
+        let header_pages = pages.iter().take_while(|page| page.granule == 0).count();
+        let (skip, pre_skip) = match pages.first() {
+            Some(first) if part_index > 0 => (header_pages, opus_pre_skip(first)),
+            _ => (0, 0),
+        };
+        let mut last_granule = granule_offset;
+        for mut page in pages.into_iter().skip(skip) {
+            page.serial = *serial.get_or_insert(page.serial);
+            page.sequence = sequence;
+            sequence += 1;
+            if page.granule != OGG_NO_GRANULE {
+                // only the first part's priming samples are skipped by the decoder
+                page.granule = (page.granule + granule_offset).saturating_sub(pre_skip);
+                last_granule = page.granule;
+            }
+            if part_index + 1 < part_count {
+                page.header_type &= !OGG_END_OF_STREAM;
+            }
+            page.write(&mut audio);
+        }
+        granule_offset = last_granule;
+    }
+    Ok(audio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(samples: &[u8]) -> Vec<u8> {
+        let mut wav = b"RIFF".to_vec();
+        wav.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&[1, 0, 1, 0, 0x80, 0x3e, 0, 0, 0, 0x7d, 0, 0, 2, 0, 16, 0]);
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+        wav.extend_from_slice(samples);
+        wav
+    }
+
+    #[test]
+    fn concatenates_riff() {
+        let audio = concatenate_audio(
+            vec![wav(&[1, 2, 3, 4]), wav(&[5, 6])],
+            AudioFormat::Riff16khz16bitMonoPcm,
+        )
+        .unwrap();
+        assert_eq!(wav(&[1, 2, 3, 4, 5, 6]), audio);
+    }
+
+    #[test]
+    fn concatenates_mp3() {
+        let tagged = [
+            b"ID3\x04\x00\x00\x00\x00\x00\x02ab".as_ref(),
+            &[0xff, 0xfb, 2],
+        ]
+        .concat();
+        let audio = concatenate_audio(
+            vec![vec![0xff, 0xfb, 1], tagged],
+            AudioFormat::Audio16khz32kbitrateMonoMp3,
+        )
+        .unwrap();
+        assert_eq!(vec![0xff, 0xfb, 1, 0xff, 0xfb, 2], audio);
+    }
+
+    /// Info frame of 128kbit/s 48kHz mono audio
+    fn info_frame() -> Vec<u8> {
+        let mut frame = vec![0; 384];
+        frame[..4].copy_from_slice(&[0xff, 0xfb, 0x94, 0xc0]);
+        frame[21..25].copy_from_slice(b"Info");
+        frame
+    }
+
+    #[test]
+    fn drops_mp3_info_frames() {
+        let id3 = b"ID3\x04\x00\x00\x00\x00\x00\x02ab".as_ref();
+        let first = [id3, &info_frame(), &[0xff, 0xfb, 1]].concat();
+        let second = [id3, &info_frame(), &[0xff, 0xfb, 2]].concat();
+        let audio = concatenate_audio(
+            vec![first, second],
+            AudioFormat::Audio48khz192kbitrateMonoMp3,
+        )
+        .unwrap();
+        assert_eq!([id3, &[0xff, 0xfb, 1, 0xff, 0xfb, 2]].concat(), audio);
+    }
+
+    fn ogg(serial: u32, packets: &[(&[u8], u64)], end: bool) -> Vec<u8> {
+        let mut data = vec![];
+        for (sequence, (packet, granule)) in packets.iter().enumerate() {
+            let header_type = if sequence == 0 {
+                0x02
+            } else if end && sequence + 1 == packets.len() {
+                OGG_END_OF_STREAM
+            } else {
+                0
+            };
+            OggPage {
+                header_type,
+                granule: *granule,
+                serial,
+                sequence: sequence as u32,
+                segment_table: &[packet.len() as u8],
+                body: packet,
+            }
+            .write(&mut data);
+        }
+        data
+    }
+
+    #[test]
+    fn concatenates_ogg() {
+        let first = ogg(
+            7,
+            &[
+                (b"OpusHead", 0),
+                (b"OpusTags", 0),
+                (b"a", 960),
+                (b"b", 1920),
+            ],
+            true,
+        );
+        let second = ogg(9, &[(b"OpusHead", 0), (b"OpusTags", 0), (b"c", 960)], true);
+        let audio =
+            concatenate_audio(vec![first, second], AudioFormat::Ogg48khz16bitMonoOpus).unwrap();
+
+        let expected = ogg(
+            7,
+            &[
+                (b"OpusHead", 0),
+                (b"OpusTags", 0),
+                (b"a", 960),
+                (b"b", 1920),
+                (b"c", 2880),
+            ],
+            true,
+        );
+        assert_eq!(expected, audio);
+    }
+
+    #[test]
+    fn concatenates_ogg_with_multi_page_tags_and_pre_skip() {
+        let head = b"OpusHead\x01\x01\x38\x01\x80\xbb\x00\x00\x00\x00\x00".as_ref();
+        let first = ogg(7, &[(head, 0), (b"OpusTags", 0), (b"a", 1272)], true);
+        let second = ogg(
+            9,
+            &[(head, 0), (b"OpusTags", 0), (b"vendor", 0), (b"b", 1272)],
+            true,
+        );
+        let audio =
+            concatenate_audio(vec![first, second], AudioFormat::Ogg48khz16bitMonoOpus).unwrap();
+
+        let expected = ogg(
+            7,
+            &[(head, 0), (b"OpusTags", 0), (b"a", 1272), (b"b", 2232)],
+            true,
+        );
+        assert_eq!(expected, audio);
+    }
+
+    #[test]
+    fn ogg_crc_matches_reference() {
+        // crc of "123456789" for the ogg variant of crc32
+        assert_eq!(0x89a1_897f, ogg_crc(b"123456789"));
+    }
+
+    #[test]
+    fn webm_is_not_supported() {
+        assert!(!supports_concatenation(AudioFormat::Webm16khz16bitMonoOpus));
+        assert!(concatenate_audio(vec![vec![1]], AudioFormat::Webm16khz16bitMonoOpus).is_err());
+    }
+}
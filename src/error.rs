@@ -1,3 +1,4 @@
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -16,4 +17,61 @@ pub enum TtsError {
     UnknownConnectionError,
     #[error("failed to renew auth token")]
     AuthenticationTimeoutFailure,
+    #[error("invalid url {0:?}")]
+    InvalidUrl(String),
+    #[error("invalid value: {0}")]
+    InvalidValue(String),
+    #[error("failed to parse xml at line {line} column {column}: {message}")]
+    XmlParseError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("failed to parse markup at line {line} column {column}: {message}")]
+    MarkupParseError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("invalid ssml: {}", display_problems(.0))]
+    InvalidSsml(Vec<ValidationProblem>),
+}
+
+/// Problem found while validating ssml
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationProblem {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub severity: Severity,
+}
+
+/// Whether a [`ValidationProblem`] keeps the ssml from being sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Malformed ssml, structure the service rejects and values outside of its limits
+    Error,
+    /// Elements, attributes and values this crate doesn't know, the service may still accept them
+    Warning,
+}
+
+impl fmt::Display for ValidationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.severity == Severity::Warning {
+            f.write_str("warning: ")?;
+        }
+        write!(
+            f,
+            "line {} column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+fn display_problems(problems: &[ValidationProblem]) -> String {
+    problems
+        .iter()
+        .map(ValidationProblem::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
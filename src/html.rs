@@ -0,0 +1,411 @@
+/// Html to ssml conversion for reading web pages aloud.
+///
+/// Scripts, navigation and hidden elements are dropped, `lang` attributes with another
+/// primary language switch language, `em`/`strong` are emphasized, `abbr` with a title is
+/// read as its title and headings, paragraphs, list items and table rows are separated by
+/// pauses. The text of other block elements is kept apart by spaces.
+use crate::{
+    ssml_serializer::VoiceSegment, BreakStrength, EmphasisLevel, Locale, Speak, VoiceSettings,
+};
+use scraper::{ElementRef, Html, Node};
+
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "iframe", "svg", "canvas",
+];
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+#[derive(Debug, Clone)]
+pub struct HtmlConverter {
+    skipped_elements: Vec<String>,
+}
+
+impl Default for HtmlConverter {
+    fn default() -> Self {
+        Self {
+            skipped_elements: SKIPPED_ELEMENTS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Speak {
+    /// Convert html using the default [`HtmlConverter`]
+    pub fn from_html(html: &str, voice_settings: &VoiceSettings) -> Speak {
+        HtmlConverter::new().convert(html, voice_settings)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Context<'a> {
+    language: &'a Locale,
+    /// Inside of a paragraph or list item, where p and s aren't allowed
+    inline: bool,
+}
+
+impl HtmlConverter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop an additional element with all of its content, for example `figure`
+    pub fn with_skipped_element(mut self, name: &str) -> Self {
+        self.skipped_elements.push(name.to_ascii_lowercase());
+        self
+    }
+
+    pub fn convert(&self, html: &str, voice_settings: &VoiceSettings) -> Speak {
+        Speak::segments_with_voice_settings(
+            voice_settings,
+            self.to_segments(html, &voice_settings.language),
+        )
+    }
+
+    /// Convert html for a voice speaking `language`
+    pub fn to_segments(&self, html: &str, language: &Locale) -> Vec<VoiceSegment> {
+        let document = Html::parse_document(html);
+        let context = Context {
+            language,
+            inline: false,
+        };
+        let mut segments = vec![];
+        self.element(document.root_element(), &context, &mut segments);
+        tidy(segments)
+    }
+
+    fn element(&self, element: ElementRef, context: &Context, output: &mut Vec<VoiceSegment>) {
+        let value = element.value();
+        let name = value.name();
+        if self.skipped_elements.iter().any(|skipped| skipped == name)
+            || value.attr("hidden").is_some()
+            || value.attr("aria-hidden") == Some("true")
+        {
+            return;
+        }
+        // tags that aren't valid BCP-47 are ignored, as are tags the voice already speaks
+        // such as `en` or `en-GB` for an `en-US` voice
+        let language = value
+            .attr("lang")
+            .and_then(|language| language.parse::<Locale>().ok());
+        match language {
+            Some(language) if language.language() != context.language.language() => {
+                let context = Context {
+                    language: &language,
+                    inline: context.inline,
+                };
+                let mut segments = vec![];
+                self.mapped_element(element, &context, &mut segments);
+                output.push(VoiceSegment::lang(&language, tidy(segments)));
+            }
+            _ => self.mapped_element(element, context, output),
+        }
+    }
+
+    fn mapped_element(
+        &self,
+        element: ElementRef,
+        context: &Context,
+        output: &mut Vec<VoiceSegment>,
+    ) {
+        let inline = Context {
+            language: context.language,
+            inline: true,
+        };
+        match element.value().name() {
+            "p" if !context.inline => {
+                let segments = tidy(self.children(element, &inline));
+                if !segments.is_empty() {
+                    output.push(VoiceSegment::paragraph(segments));
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = match element.value().name() {
+                    "h1" | "h2" => EmphasisLevel::Strong,
+                    _ => EmphasisLevel::Moderate,
+                };
+                let segments = tidy(self.children(element, &inline));
+                if !segments.is_empty() {
+                    output.push(VoiceSegment::emphasis(level, segments));
+                    output.push(VoiceSegment::pause_with_strength(BreakStrength::Strong));
+                }
+            }
+            "li" if !context.inline => {
+                // nested lists can't be part of the item sentence
+                let mut segments = vec![];
+                let mut nested = vec![];
+                for child in element.children() {
+                    match ElementRef::wrap(child) {
+                        Some(list) if matches!(list.value().name(), "ul" | "ol") => {
+                            self.element(list, context, &mut nested)
+                        }
+                        _ => self.node(child, &inline, &mut segments),
+                    }
+                }
+                let segments = tidy(segments);
+                if !segments.is_empty() {
+                    output.push(VoiceSegment::sentence(segments));
+                }
+                output.extend(nested);
+            }
+            "tr" if !context.inline => {
+                let segments = tidy(self.children(element, &inline));
+                if !segments.is_empty() {
+                    output.push(VoiceSegment::sentence(segments));
+                }
+            }
+            "em" | "i" => output.push(VoiceSegment::emphasis(
+                EmphasisLevel::Moderate,
+                tidy(self.children(element, &inline)),
+            )),
+            "strong" | "b" => output.push(VoiceSegment::emphasis(
+                EmphasisLevel::Strong,
+                tidy(self.children(element, &inline)),
+            )),
+            "abbr" if element.value().attr("title").is_some() => {
+                let text = collapse_whitespace(&element.text().collect::<String>());
+                let alias = element.value().attr("title").unwrap_or_default();
+                output.push(VoiceSegment::sub(text.trim(), alias.trim()));
+            }
+            "br" => push_text(output, " "),
+            name => {
+                // block content must not run into the text around it
+                let is_block = BLOCK_ELEMENTS.contains(&name);
+                if is_block {
+                    push_text(output, " ");
+                }
+                for segment in self.children(element, context) {
+                    match segment {
+                        VoiceSegment::Plain(text) => push_text(output, &text),
+                        segment => output.push(segment),
+                    }
+                }
+                if is_block {
+                    push_text(output, " ");
+                }
+            }
+        }
+    }
+
+    fn children(&self, element: ElementRef, context: &Context) -> Vec<VoiceSegment> {
+        let mut segments = vec![];
+        for child in element.children() {
+            self.node(child, context, &mut segments);
+        }
+        segments
+    }
+
+    fn node(
+        &self,
+        node: ego_tree::NodeRef<Node>,
+        context: &Context,
+        output: &mut Vec<VoiceSegment>,
+    ) {
+        match node.value() {
+            Node::Text(text) => push_text(output, &collapse_whitespace(text)),
+            Node::Element(_) => {
+                if let Some(element) = ElementRef::wrap(node) {
+                    self.element(element, context, output)
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    for character in text.chars() {
+        if character.is_whitespace() {
+            if !collapsed.ends_with(' ') {
+                collapsed.push(' ');
+            }
+        } else {
+            collapsed.push(character);
+        }
+    }
+    collapsed
+}
+
+/// Append text merging whitespace with the previous text segment
+fn push_text(segments: &mut Vec<VoiceSegment>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(VoiceSegment::Plain(previous)) = segments.last_mut() {
+        if previous.ends_with(' ') {
+            previous.push_str(text.trim_start_matches(' '));
+        } else {
+            previous.push_str(text);
+        }
+    } else {
+        segments.push(VoiceSegment::plain(text));
+    }
+}
+
+/// Trim whitespace at the edges and around block segments
+fn tidy(segments: Vec<VoiceSegment>) -> Vec<VoiceSegment> {
+    let is_block = |segment: Option<&VoiceSegment>| {
+        matches!(
+            segment,
+            None | Some(VoiceSegment::Paragraph(_))
+                | Some(VoiceSegment::Sentence(_))
+                | Some(VoiceSegment::Break(_))
+        )
+    };
+    let mut tidied: Vec<VoiceSegment> = Vec::with_capacity(segments.len());
+    let mut segments = segments.into_iter().peekable();
+    while let Some(segment) = segments.next() {
+        match segment {
+            VoiceSegment::Plain(mut text) => {
+                if is_block(tidied.last()) {
+                    text = text.trim_start().to_owned();
+                }
+                if is_block(segments.peek()) {
+                    text = text.trim_end().to_owned();
+                }
+                if !text.is_empty() {
+                    tidied.push(VoiceSegment::Plain(text));
+                }
+            }
+            segment => tidied.push(segment),
+        }
+    }
+    tidied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnUsVoices;
+
+    #[test]
+    fn converts_article() {
+        let html = r#"<!DOCTYPE html>
+<html lang="en-US">
+<head><title>Ignored</title><script>alert("no")</script></head>
+<body>
+  <nav><a href="/">Home</a></nav>
+  <h1>Release  notes</h1>
+  <p><span>We</span> <em>finally</em> support <abbr title="Speech Synthesis Markup Language">SSML</abbr>.
+     <span lang="fr-FR">Bonjour</span>!</p>
+  <ul>
+    <li>One<ul><li>Nested</li></ul></li>
+    <li><p>Two</p></li>
+  </ul>
+  <footer>Copyright</footer>
+</body>
+</html>"#;
+        let speak = Speak::from_html(html, &EnUsVoices::JennyNeural.to_voice_settings());
+        let expected = Speak::segments_with_voice_settings(
+            &EnUsVoices::JennyNeural.to_voice_settings(),
+            vec![
+                VoiceSegment::emphasis(
+                    EmphasisLevel::Strong,
+                    vec![VoiceSegment::plain("Release notes")],
+                ),
+                VoiceSegment::pause_with_strength(BreakStrength::Strong),
+                VoiceSegment::paragraph(vec![
+                    VoiceSegment::plain("We "),
+                    VoiceSegment::emphasis(
+                        EmphasisLevel::Moderate,
+                        vec![VoiceSegment::plain("finally")],
+                    ),
+                    VoiceSegment::plain(" support "),
+                    VoiceSegment::sub("SSML", "Speech Synthesis Markup Language"),
+                    VoiceSegment::plain(". "),
+                    VoiceSegment::lang(
+                        &"fr-FR".parse().unwrap(),
+                        vec![VoiceSegment::plain("Bonjour")],
+                    ),
+                    VoiceSegment::plain("!"),
+                ]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("One")]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("Nested")]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("Two")]),
+            ],
+        );
+        assert_eq!(expected, speak);
+        speak.validate().unwrap();
+    }
+
+    #[test]
+    fn skips_hidden_and_configured_elements() {
+        let converter = HtmlConverter::new().with_skipped_element("figure");
+        let segments = converter.to_segments(
+            "<p>Visible<span hidden>hidden</span><span aria-hidden=\"true\">icon</span></p>\
+             <figure>Chart</figure>",
+            &"en-US".parse().unwrap(),
+        );
+        assert_eq!(
+            vec![VoiceSegment::paragraph(vec![VoiceSegment::plain(
+                "Visible"
+            )])],
+            segments
+        );
+    }
+
+    #[test]
+    fn separates_block_elements() {
+        let segments = HtmlConverter::new().to_segments(
+            "<div>First block</div><div>Second block</div>\
+             <table><tr><td>a</td><td>b</td></tr><tr><th>c</th></tr></table>",
+            &"en-US".parse().unwrap(),
+        );
+        assert_eq!(
+            vec![
+                VoiceSegment::plain("First block Second block"),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("a b")]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("c")]),
+            ],
+            segments
+        );
+    }
+
+    #[test]
+    fn keeps_voice_for_same_primary_language() {
+        let segments = HtmlConverter::new().to_segments(
+            "<html lang=\"en\"><body><p>Hello <span lang=\"en-GB\">mate</span> \
+             <span lang=\"de\">Tschüss</span></p></body></html>",
+            &"en-US".parse().unwrap(),
+        );
+        assert_eq!(
+            vec![VoiceSegment::paragraph(vec![
+                VoiceSegment::plain("Hello mate "),
+                VoiceSegment::lang(&"de".parse().unwrap(), vec![VoiceSegment::plain("Tschüss")]),
+            ])],
+            segments
+        );
+    }
+}
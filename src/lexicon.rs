@@ -0,0 +1,346 @@
+/// Custom lexicon in the W3C Pronunciation Lexicon Specification (PLS) format.
+///
+/// Host the generated document somewhere the service can reach it and reference it using [`crate::Speak::with_lexicon`].
+use crate::{
+    xml::{parse_error, XmlWriter},
+    Locale, PhoneticAlphabet, Result,
+};
+use quick_xml::{events::Event, Reader};
+
+const PLS_VERSION: &str = "1.0";
+const PLS_XMLNS_LINK: &str = "http://www.w3.org/2005/01/pronunciation-lexicon";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexicon {
+    pub language: Locale,
+    pub alphabet: PhoneticAlphabet,
+    pub lexemes: Vec<Lexeme>,
+}
+
+impl Lexicon {
+    pub fn new(language: &Locale) -> Self {
+        Self {
+            language: language.clone(),
+            alphabet: PhoneticAlphabet::Ipa,
+            lexemes: vec![],
+        }
+    }
+
+    pub fn with_alphabet(mut self, alphabet: PhoneticAlphabet) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    pub fn with_alias(self, grapheme: &str, alias: &str) -> Self {
+        self.with_lexeme(Lexeme::alias(grapheme, alias))
+    }
+
+    pub fn with_phoneme(self, grapheme: &str, phoneme: &str) -> Self {
+        self.with_lexeme(Lexeme::phoneme(grapheme, phoneme))
+    }
+
+    pub fn with_lexeme(mut self, lexeme: Lexeme) -> Self {
+        self.lexemes.push(lexeme);
+        self
+    }
+
+    pub fn to_pls_xml(&self) -> String {
+        let mut writer = XmlWriter::new();
+        writer.declaration();
+        writer.start(
+            "lexicon",
+            &[
+                ("version", PLS_VERSION),
+                ("xmlns", PLS_XMLNS_LINK),
+                ("alphabet", self.alphabet.as_pls_string()),
+                ("xml:lang", self.language.as_str()),
+            ],
+        );
+        for lexeme in &self.lexemes {
+            writer.start("lexeme", &[]);
+            for grapheme in &lexeme.graphemes {
+                writer.text_element("grapheme", grapheme);
+            }
+            for phoneme in &lexeme.phonemes {
+                writer.text_element("phoneme", phoneme);
+            }
+            for alias in &lexeme.aliases {
+                writer.text_element("alias", alias);
+            }
+            writer.end("lexeme");
+        }
+        writer.end("lexicon");
+        writer.into_string()
+    }
+
+    pub fn from_pls_str(pls: &str) -> Result<Self> {
+        let mut reader = Reader::from_str(pls);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+        let mut skip_buf = Vec::new();
+        let mut lexicon: Option<Lexicon> = None;
+        let mut lexeme: Option<Lexeme> = None;
+        let mut field: Option<(LexemeField, String)> = None;
+        loop {
+            let position = reader.buffer_position();
+            match reader.read_event(&mut buf) {
+                Ok(Event::Start(ref element)) | Ok(Event::Empty(ref element))
+                    if element.local_name() == b"lexicon" =>
+                {
+                    if lexicon.is_some() {
+                        return Err(parse_error(pls, position, "nested lexicon element"));
+                    }
+                    let mut language = None;
+                    let mut alphabet = PhoneticAlphabet::Ipa;
+                    for attribute in element.attributes() {
+                        let attribute = attribute.map_err(|e| parse_error(pls, position, e))?;
+                        let value = attribute
+                            .unescape_and_decode_value(&reader)
+                            .map_err(|e| parse_error(pls, position, e))?;
+                        match attribute.key {
+                            b"xml:lang" => {
+                                language = Some(
+                                    value
+                                        .parse::<Locale>()
+                                        .map_err(|error| parse_error(pls, position, error))?,
+                                )
+                            }
+                            b"alphabet" => {
+                                alphabet = value
+                                    .parse::<PhoneticAlphabet>()
+                                    .map_err(|error| parse_error(pls, position, error))?
+                            }
+                            _ => (),
+                        }
+                    }
+                    let language = language
+                        .ok_or_else(|| parse_error(pls, position, "lexicon without xml:lang"))?;
+                    lexicon = Some(Lexicon::new(&language).with_alphabet(alphabet));
+                }
+                Ok(Event::Start(ref element)) if element.local_name() == b"lexeme" => {
+                    if lexicon.is_none() || lexeme.is_some() {
+                        return Err(parse_error(pls, position, "unexpected lexeme element"));
+                    }
+                    lexeme = Some(Lexeme::default());
+                }
+                Ok(Event::Start(ref element)) => {
+                    let kind = match element.local_name() {
+                        b"grapheme" => LexemeField::Grapheme,
+                        b"phoneme" => LexemeField::Phoneme,
+                        b"alias" => LexemeField::Alias,
+                        _ => {
+                            // metadata and examples are not used for synthesis
+                            let name = element.name().to_vec();
+                            reader
+                                .read_to_end(name, &mut skip_buf)
+                                .map_err(|e| parse_error(pls, reader.buffer_position(), e))?;
+                            continue;
+                        }
+                    };
+                    if lexeme.is_none() || field.is_some() {
+                        return Err(parse_error(
+                            pls,
+                            position,
+                            format!("unexpected {} element", kind.as_string()),
+                        ));
+                    }
+                    field = Some((kind, String::new()));
+                }
+                Ok(Event::Text(ref text)) => {
+                    if let Some((_, value)) = field.as_mut() {
+                        let text = text
+                            .unescape_and_decode(&reader)
+                            .map_err(|e| parse_error(pls, position, e))?;
+                        value.push_str(&text);
+                    }
+                }
+                Ok(Event::End(ref element)) => match element.local_name() {
+                    b"grapheme" | b"phoneme" | b"alias" => {
+                        let (kind, value) = field
+                            .take()
+                            .ok_or_else(|| parse_error(pls, position, "unexpected end tag"))?;
+                        // lexeme is always present when field is set
+                        let current = lexeme.as_mut().expect("field outside of lexeme");
+                        let value = value.trim().to_owned();
+                        match kind {
+                            LexemeField::Grapheme => current.graphemes.push(value),
+                            LexemeField::Phoneme => current.phonemes.push(value),
+                            LexemeField::Alias => current.aliases.push(value),
+                        }
+                    }
+                    b"lexeme" => {
+                        let finished = lexeme
+                            .take()
+                            .ok_or_else(|| parse_error(pls, position, "unexpected end tag"))?;
+                        if finished.graphemes.is_empty() {
+                            return Err(parse_error(pls, position, "lexeme without grapheme"));
+                        }
+                        if finished.phonemes.is_empty() && finished.aliases.is_empty() {
+                            return Err(parse_error(
+                                pls,
+                                position,
+                                "lexeme without phoneme or alias",
+                            ));
+                        }
+                        if let Some(lexicon) = lexicon.as_mut() {
+                            lexicon.lexemes.push(finished);
+                        }
+                    }
+                    _ => (),
+                },
+                Ok(Event::Eof) => break,
+                Ok(_) => (),
+                Err(error) => return Err(parse_error(pls, reader.buffer_position(), error)),
+            }
+            buf.clear();
+        }
+        lexicon.ok_or_else(|| parse_error(pls, pls.len(), "missing lexicon element"))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lexeme {
+    pub graphemes: Vec<String>,
+    pub phonemes: Vec<String>,
+    pub aliases: Vec<String>,
+}
+
+impl Lexeme {
+    pub fn alias(grapheme: &str, alias: &str) -> Self {
+        Self {
+            graphemes: vec![grapheme.to_owned()],
+            phonemes: vec![],
+            aliases: vec![alias.to_owned()],
+        }
+    }
+
+    pub fn phoneme(grapheme: &str, phoneme: &str) -> Self {
+        Self {
+            graphemes: vec![grapheme.to_owned()],
+            phonemes: vec![phoneme.to_owned()],
+            aliases: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LexemeField {
+    Grapheme,
+    Phoneme,
+    Alias,
+}
+
+impl LexemeField {
+    fn as_string(&self) -> &'static str {
+        match self {
+            LexemeField::Grapheme => "grapheme",
+            LexemeField::Phoneme => "phoneme",
+            LexemeField::Alias => "alias",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TtsError;
+
+    #[test]
+    fn pls_serialization() {
+        let lexicon = Lexicon::new(&"en-US".parse().unwrap())
+            .with_alias("BTW", "By the way")
+            .with_phoneme("Benigni", "bɛˈniːnji");
+
+        let expected = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+<lexicon version=\"1.0\" xmlns=\"http://www.w3.org/2005/01/pronunciation-lexicon\" alphabet=\"ipa\" xml:lang=\"en-US\">\
+<lexeme><grapheme>BTW</grapheme><alias>By the way</alias></lexeme>\
+<lexeme><grapheme>Benigni</grapheme><phoneme>bɛˈniːnji</phoneme></lexeme>\
+</lexicon>";
+        assert_eq!(expected, lexicon.to_pls_xml());
+    }
+
+    #[test]
+    fn pls_parsing() {
+        let pls = r#"<?xml version="1.0" encoding="UTF-8"?>
+<lexicon version="1.0"
+      xmlns="http://www.w3.org/2005/01/pronunciation-lexicon"
+      xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+      xsi:schemaLocation="http://www.w3.org/2005/01/pronunciation-lexicon
+        http://www.w3.org/TR/2007/CR-pronunciation-lexicon-20071212/pls.xsd"
+      alphabet="x-microsoft-sapi" xml:lang="en-GB">
+  <metadata><dc:title>Example</dc:title></metadata>
+  <lexeme>
+    <grapheme>Scotland MoD</grapheme>
+    <grapheme>Scotland Ministry of Defence</grapheme>
+    <alias>Scotland Ministry of Defence</alias>
+  </lexeme>
+  <lexeme>
+    <grapheme> Benigni </grapheme>
+    <phoneme>b eh 1 n iy 0 n y iy 0</phoneme>
+  </lexeme>
+</lexicon>"#;
+
+        let lexicon = Lexicon::from_pls_str(pls).unwrap();
+        let expected = Lexicon::new(&"en-GB".parse().unwrap())
+            .with_alphabet(PhoneticAlphabet::Sapi)
+            .with_lexeme(Lexeme {
+                graphemes: vec![
+                    "Scotland MoD".to_owned(),
+                    "Scotland Ministry of Defence".to_owned(),
+                ],
+                phonemes: vec![],
+                aliases: vec!["Scotland Ministry of Defence".to_owned()],
+            })
+            .with_phoneme("Benigni", "b eh 1 n iy 0 n y iy 0");
+        assert_eq!(expected, lexicon);
+    }
+
+    #[test]
+    fn pls_microsoft_alphabet() {
+        let lexicon = Lexicon::new(&"en-US".parse().unwrap())
+            .with_alphabet(PhoneticAlphabet::Sapi)
+            .with_phoneme("Benigni", "b eh 1 n iy 0 n y iy 0");
+        let pls = lexicon.to_pls_xml();
+        assert!(pls.contains("alphabet=\"x-microsoft-sapi\""));
+        assert_eq!(lexicon, Lexicon::from_pls_str(&pls).unwrap());
+        let lexicon = Lexicon::new(&"en-US".parse().unwrap()).with_alphabet(PhoneticAlphabet::Ups);
+        assert!(lexicon
+            .to_pls_xml()
+            .contains("alphabet=\"x-microsoft-ups\""));
+    }
+
+    #[test]
+    fn pls_round_trip() {
+        let lexicon = Lexicon::new(&"en-US".parse().unwrap())
+            .with_alias("R&D", "research and development")
+            .with_phoneme("tomato", "təˈmɑːtoʊ");
+        let parsed = Lexicon::from_pls_str(&lexicon.to_pls_xml()).unwrap();
+        assert_eq!(lexicon, parsed);
+    }
+
+    #[test]
+    fn pls_lexeme_without_pronunciation() {
+        let pls = "<lexicon version=\"1.0\" alphabet=\"ipa\" xml:lang=\"en-US\">\n\
+<lexeme><grapheme>BTW</grapheme></lexeme>\n\
+</lexicon>";
+        match Lexicon::from_pls_str(pls) {
+            Err(TtsError::XmlParseError { line, .. }) => assert_eq!(2, line),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pls_language() {
+        for pls in [
+            "<lexicon version=\"1.0\" alphabet=\"ipa\"></lexicon>",
+            "<lexicon version=\"1.0\" alphabet=\"ipa\" xml:lang=\"english\"></lexicon>",
+        ] {
+            assert!(Lexicon::from_pls_str(pls).is_err(), "{pls}");
+        }
+        let pls = "<lexicon version=\"1.0\" alphabet=\"ipa\" xml:lang=\"en_gb\"></lexicon>";
+        assert_eq!(
+            "en-GB",
+            Lexicon::from_pls_str(pls).unwrap().language.as_str()
+        );
+    }
+}
@@ -1,24 +1,76 @@
+mod audio;
 mod error;
+#[cfg(feature = "html")]
+mod html;
+mod lexicon;
+mod locale;
+mod long_text;
+#[cfg(feature = "macros")]
+mod macros;
+#[cfg(feature = "markdown")]
+mod markdown;
+mod markup;
+mod normalizer;
+mod pronunciation;
+mod segmenter;
+mod spoken_text;
+mod ssml_builder;
+mod ssml_parser;
 mod ssml_serializer;
+mod ssml_validator;
+mod streaming;
 mod types;
+mod voice_catalog;
+mod xml;
 
-use std::time::{Duration, Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use bytes::Buf;
-pub use error::TtsError;
-pub use ssml_serializer::{Speak, VoiceSegment};
+use futures::{lock::Mutex, stream, Stream, StreamExt, TryStreamExt};
+
+pub use audio::{concatenate_audio, supports_concatenation};
+pub use error::{Severity, TtsError, ValidationProblem};
+#[cfg(feature = "html")]
+pub use html::HtmlConverter;
+pub use lexicon::{Lexeme, Lexicon};
+pub use locale::Locale;
+pub use long_text::LongTextSettings;
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub use macros::__private;
+#[cfg(feature = "markdown")]
+pub use markdown::{CodeSpans, MarkdownConverter};
+pub use normalizer::{EmojiHandling, TextNormalizer};
+pub use pronunciation::{Pronunciation, PronunciationDictionary, PronunciationEntry};
+pub use segmenter::SentenceSegmenter;
+pub use spoken_text::{spoken_text, SpokenText, TextSpan};
+pub use ssml_builder::{SegmentBuilder, SpeakBuilder};
+pub use ssml_serializer::{
+    BackgroundAudio, Expression, ProsodySettings, Speak, Voice, VoiceSegment,
+};
+pub use ssml_validator::{check_ssml, validate_ssml, MAX_REQUEST_SIZE, MAX_VOICE_ELEMENTS};
+pub use streaming::StreamingSettings;
 pub use types::*;
+pub use voice_catalog::{VoiceCatalog, VoiceQuery};
 
 type Result<T> = std::result::Result<T, TtsError>;
 
 pub struct VoiceService {
     service_region: Region,
     subscription_key: String,
-    access_token: Option<String>,
-    access_toke_time: Instant,
+    /// Shared with the phrases of streams, which renew it once it expires
+    access_token: Arc<Mutex<Option<AccessToken>>>,
     https_client: reqwest::Client,
 }
 
+struct AccessToken {
+    token: String,
+    issued: Instant,
+}
+
 /// Timeout is 10 minutes
 const ACCESS_TOKEN_TIMEOUT: Duration = Duration::from_secs(60 * 9);
 
@@ -29,28 +81,25 @@ impl VoiceService {
         Self {
             service_region,
             subscription_key: subscription_key.to_owned(),
-            access_token: None,
-            access_toke_time: Instant::now(),
+            access_token: Arc::new(Mutex::new(None)),
             https_client,
         }
     }
 
+    /// Voices available in the region
+    ///
+    /// Entries that can't be parsed, for example because of a change in the service, are skipped.
+    /// Use [`VoiceDescription::from_entries`] on the raw list to inspect them.
     pub async fn list_voices(&mut self) -> Result<Vec<VoiceDescription>> {
-        self.renew_token_if_expired().await?;
+        let bearer_token = self.bearer_token().await?;
         let region_host_name = format!(
             "{}.tts.speech.microsoft.com",
             self.service_region.as_string()
         );
         let endpoint = format!("https://{}/cognitiveservices/voices/list", region_host_name);
-        let bearer_token = format!(
-            "Bearer: {}",
-            self.access_token
-                .as_ref()
-                .ok_or(TtsError::AuthenticationTimeoutFailure)?
-        );
 
         // this can auth using either access token or sub key
-        let response = self
+        let entries: Vec<serde_json::Value> = self
             .https_client
             .get(endpoint)
             // .header("Ocp-Apim-Subscription-Key", &self.subscription_key)
@@ -60,48 +109,29 @@ impl VoiceService {
             .await?
             .json()
             .await?;
-        Ok(response)
+        Ok(VoiceDescription::from_entries(entries).0)
+    }
+
+    /// [`Self::list_voices`] as a [`VoiceCatalog`] for queries
+    pub async fn voice_catalog(&mut self) -> Result<VoiceCatalog> {
+        Ok(VoiceCatalog::new(self.list_voices().await?))
     }
 
+    /// Invalid ssml is reported as [`TtsError::InvalidSsml`] without sending a request
     pub async fn synthesize_raw_text(
         &mut self,
         text: String,
         audio_format: AudioFormat,
     ) -> Result<Vec<u8>> {
-        self.renew_token_if_expired().await?;
-        let endpoint = format!(
-            "https://{}.tts.speech.microsoft.com/cognitiveservices/v1",
-            self.service_region.as_string()
-        );
-        let bearer_token = format!(
-            "Bearer: {}",
-            self.access_token
-                .as_ref()
-                .ok_or(TtsError::AuthenticationTimeoutFailure)?
-        );
-
-        let response = self
-            .https_client
-            .post(endpoint)
-            .header("Authorization", bearer_token)
-            .header("X-Microsoft-OutputFormat", audio_format.as_string())
-            .header("Content-Type", "application/ssml+xml")
-            .header("User-Agent", "rust-azure-tts-client-lib")
-            .body(text)
-            .send()
-            .await?;
-        match response.status() {
-            reqwest::StatusCode::OK => (),
-            reqwest::StatusCode::BAD_REQUEST => return Err(TtsError::BadRequest),
-            reqwest::StatusCode::UNAUTHORIZED => return Err(TtsError::AuthError),
-            reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE => {
-                return Err(TtsError::UnsupportedMediaType)
-            }
-            reqwest::StatusCode::TOO_MANY_REQUESTS => return Err(TtsError::TooManyRequest),
-            _ => return Err(TtsError::UnknownConnectionError),
-        }
-        let audio = response.bytes().await?;
-        Ok(audio.chunk().to_vec())
+        let bearer_token = self.bearer_token().await?;
+        post_ssml(
+            &self.https_client,
+            self.service_region,
+            &bearer_token,
+            text,
+            audio_format,
+        )
+        .await
     }
 
     pub async fn synthesize(
@@ -110,40 +140,8 @@ impl VoiceService {
         voice: &VoiceSettings,
         audio_format: AudioFormat,
     ) -> Result<Vec<u8>> {
-        self.renew_token_if_expired().await?;
-        let endpoint = format!(
-            "https://{}.tts.speech.microsoft.com/cognitiveservices/v1",
-            self.service_region.as_string()
-        );
-        let bearer_token = format!(
-            "Bearer: {}",
-            self.access_token
-                .as_ref()
-                .ok_or(TtsError::AuthenticationTimeoutFailure)?
-        );
-
-        let response = self
-            .https_client
-            .post(endpoint)
-            .header("Authorization", bearer_token)
-            .header("X-Microsoft-OutputFormat", audio_format.as_string())
-            .header("Content-Type", "application/ssml+xml")
-            .header("User-Agent", "rust-azure-tts-client-lib")
-            .body(Speak::text_with_voice_settings(voice, text).to_ssml_xml())
-            .send()
-            .await?;
-        match response.status() {
-            reqwest::StatusCode::OK => (),
-            reqwest::StatusCode::BAD_REQUEST => return Err(TtsError::BadRequest),
-            reqwest::StatusCode::UNAUTHORIZED => return Err(TtsError::AuthError),
-            reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE => {
-                return Err(TtsError::UnsupportedMediaType)
-            }
-            reqwest::StatusCode::TOO_MANY_REQUESTS => return Err(TtsError::TooManyRequest),
-            _ => return Err(TtsError::UnknownConnectionError),
-        }
-        let audio = response.bytes().await?;
-        Ok(audio.chunk().to_vec())
+        let ssml = Speak::text_with_voice_settings(voice, text).to_ssml_xml();
+        self.synthesize_raw_text(ssml, audio_format).await
     }
 
     pub async fn synthesize_segments(
@@ -152,68 +150,231 @@ impl VoiceService {
         voice: &VoiceSettings,
         audio_format: AudioFormat,
     ) -> Result<Vec<u8>> {
-        self.renew_token_if_expired().await?;
-        let endpoint = format!(
-            "https://{}.tts.speech.microsoft.com/cognitiveservices/v1",
-            self.service_region.as_string()
-        );
-        let bearer_token = format!(
-            "Bearer: {}",
-            self.access_token
-                .as_ref()
-                .ok_or(TtsError::AuthenticationTimeoutFailure)?
-        );
+        let ssml = Speak::segments_with_voice_settings(voice, segments).to_ssml_xml();
+        self.synthesize_raw_text(ssml, audio_format).await
+    }
 
-        let response = self
-            .https_client
-            .post(endpoint)
-            .header("Authorization", bearer_token)
-            .header("X-Microsoft-OutputFormat", audio_format.as_string())
-            .header("Content-Type", "application/ssml+xml")
-            .header("User-Agent", "rust-azure-tts-client-lib")
-            .body(Speak::segments_with_voice_settings(voice, segments).to_ssml_xml())
-            .send()
-            .await?;
-        match response.status() {
-            reqwest::StatusCode::OK => (),
-            reqwest::StatusCode::BAD_REQUEST => return Err(TtsError::BadRequest),
-            reqwest::StatusCode::UNAUTHORIZED => return Err(TtsError::AuthError),
-            reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE => {
-                return Err(TtsError::UnsupportedMediaType)
-            }
-            reqwest::StatusCode::TOO_MANY_REQUESTS => return Err(TtsError::TooManyRequest),
-            _ => return Err(TtsError::UnknownConnectionError),
+    pub async fn synthesize_speak(
+        &mut self,
+        speak: &Speak,
+        audio_format: AudioFormat,
+    ) -> Result<Vec<u8>> {
+        self.synthesize_raw_text(speak.to_ssml_xml(), audio_format)
+            .await
+    }
+
+    /// Synthesize multiple voices, for example a dialogue, in a single request
+    pub async fn synthesize_voices(
+        &mut self,
+        voices: Vec<(VoiceSettings, Vec<VoiceSegment>)>,
+        audio_format: AudioFormat,
+    ) -> Result<Vec<u8>> {
+        let speak = Speak::voices_with_voice_settings(voices);
+        self.synthesize_speak(&speak, audio_format).await
+    }
+
+    /// Synthesize text too long for a single request
+    ///
+    /// Text is split into sentence aligned chunks which are synthesized concurrently
+    /// and joined in order using [`concatenate_audio`]. Webm output can't be joined.
+    pub async fn synthesize_long(
+        &mut self,
+        text: &str,
+        voice: &VoiceSettings,
+        audio_format: AudioFormat,
+        settings: LongTextSettings,
+    ) -> Result<Vec<u8>> {
+        if !supports_concatenation(audio_format) {
+            return Err(TtsError::InvalidValue(format!(
+                "can't join {} audio from multiple requests",
+                audio_format.as_string()
+            )));
         }
-        let audio = response.bytes().await?;
-        Ok(audio.chunk().to_vec())
+        let bearer_token = self.bearer_token().await?;
+        let segmenter = SentenceSegmenter::for_language(&voice.language);
+        let requests = long_text::split_chunks(text, settings.max_chunk_size, &segmenter)
+            .into_iter()
+            .map(|chunk| {
+                post_ssml(
+                    &self.https_client,
+                    self.service_region,
+                    &bearer_token,
+                    Speak::text_with_voice_settings(voice, &chunk).to_ssml_xml(),
+                    audio_format,
+                )
+            });
+        let parts: Vec<Vec<u8>> = stream::iter(requests)
+            .buffered(settings.max_concurrent_requests.max(1))
+            .try_collect()
+            .await?;
+        concatenate_audio(parts, audio_format)
     }
 
-    pub async fn update_auth_token(&mut self) -> Result<()> {
-        let region_host_name = format!(
-            "{}.api.cognitive.microsoft.com",
-            self.service_region.as_string()
+    /// Synthesize text as it arrives, for example tokens streamed from a language model
+    ///
+    /// Text is buffered until a sentence, or a clause of a long sentence, is complete.
+    /// Phrases are synthesized concurrently as soon as they are complete and audio is yielded in order,
+    /// one chunk per phrase. Chunks are complete files in `audio_format`, use raw formats
+    /// or [`concatenate_audio`] to play them as a single stream. A phrase that fails yields
+    /// its error in place of its chunk, later phrases are still synthesized.
+    ///
+    /// The access token is renewed for each phrase once it expires, so streams may run for
+    /// longer than a token is valid.
+    pub async fn synthesize_stream<S>(
+        &mut self,
+        text: S,
+        voice: &VoiceSettings,
+        audio_format: AudioFormat,
+        settings: StreamingSettings,
+    ) -> Result<impl Stream<Item = Result<Vec<u8>>>>
+    where
+        S: Stream<Item = String>,
+    {
+        // fail before streaming when the service can't authenticate
+        self.bearer_token().await?;
+        let https_client = self.https_client.clone();
+        let service_region = self.service_region;
+        let subscription_key = self.subscription_key.clone();
+        let access_token = self.access_token.clone();
+        let voice = voice.clone();
+        let segmenter = SentenceSegmenter::for_language(&voice.language);
+        let phrases = streaming::phrases(text, segmenter, settings.clause_length);
+        let audio = streaming::synthesize_phrases(
+            phrases,
+            settings.max_concurrent_requests,
+            move |phrase| {
+                let https_client = https_client.clone();
+                let subscription_key = subscription_key.clone();
+                let access_token = access_token.clone();
+                let ssml = Speak::text_with_voice_settings(&voice, &phrase).to_ssml_xml();
+                async move {
+                    let bearer_token = renewed_bearer_token(
+                        &https_client,
+                        service_region,
+                        &subscription_key,
+                        &access_token,
+                    )
+                    .await?;
+                    post_ssml(
+                        &https_client,
+                        service_region,
+                        &bearer_token,
+                        ssml,
+                        audio_format,
+                    )
+                    .await
+                }
+            },
         );
-        let endpoint = format!("https://{}/sts/v1.0/issuetoken", region_host_name);
-        let response = self
-            .https_client
-            .post(endpoint)
-            .header("Ocp-Apim-Subscription-Key", &self.subscription_key)
-            .header("Host", region_host_name)
-            .header("Content-type", "application/x-www-form-urlencoded")
-            .header("Content-Length", "0")
-            .send()
-            .await?
-            .text()
-            .await?;
-        self.access_token = Some(response);
-        self.access_toke_time = Instant::now();
-        Ok(())
+        Ok(audio)
     }
 
-    async fn renew_token_if_expired(&mut self) -> Result<()> {
-        if self.access_token.is_none() || self.access_toke_time.elapsed() > ACCESS_TOKEN_TIMEOUT {
-            self.update_auth_token().await?;
-        }
+    pub async fn update_auth_token(&mut self) -> Result<()> {
+        let token = issue_token(
+            &self.https_client,
+            self.service_region,
+            &self.subscription_key,
+        )
+        .await?;
+        *self.access_token.lock().await = Some(AccessToken {
+            token,
+            issued: Instant::now(),
+        });
         Ok(())
     }
+
+    async fn bearer_token(&self) -> Result<String> {
+        renewed_bearer_token(
+            &self.https_client,
+            self.service_region,
+            &self.subscription_key,
+            &self.access_token,
+        )
+        .await
+    }
+}
+
+async fn issue_token(
+    https_client: &reqwest::Client,
+    service_region: Region,
+    subscription_key: &str,
+) -> Result<String> {
+    let region_host_name = format!("{}.api.cognitive.microsoft.com", service_region.as_string());
+    let endpoint = format!("https://{}/sts/v1.0/issuetoken", region_host_name);
+    let response = https_client
+        .post(endpoint)
+        .header("Ocp-Apim-Subscription-Key", subscription_key)
+        .header("Host", region_host_name)
+        .header("Content-type", "application/x-www-form-urlencoded")
+        .header("Content-Length", "0")
+        .send()
+        .await?
+        .text()
+        .await?;
+    Ok(response)
+}
+
+/// Bearer token for a request, the access token is renewed first if it's missing or expired
+///
+/// Doesn't borrow the service, so requests outliving it such as the phrases of a stream renew
+/// the shared token too.
+async fn renewed_bearer_token(
+    https_client: &reqwest::Client,
+    service_region: Region,
+    subscription_key: &str,
+    access_token: &Mutex<Option<AccessToken>>,
+) -> Result<String> {
+    // concurrent requests wait for a single renewal
+    let mut access_token = access_token.lock().await;
+    if access_token
+        .as_ref()
+        .is_none_or(|access_token| access_token.issued.elapsed() > ACCESS_TOKEN_TIMEOUT)
+    {
+        let token = issue_token(https_client, service_region, subscription_key).await?;
+        *access_token = Some(AccessToken {
+            token,
+            issued: Instant::now(),
+        });
+    }
+    let access_token = access_token
+        .as_ref()
+        .ok_or(TtsError::AuthenticationTimeoutFailure)?;
+    Ok(format!("Bearer: {}", access_token.token))
+}
+
+/// Synthesize ssml without borrowing the service mutably, allows concurrent requests
+///
+/// The ssml is checked with [`validate_ssml`] before it's sent, warnings don't keep it from
+/// being sent.
+async fn post_ssml(
+    https_client: &reqwest::Client,
+    service_region: Region,
+    bearer_token: &str,
+    ssml: String,
+    audio_format: AudioFormat,
+) -> Result<Vec<u8>> {
+    validate_ssml(&ssml)?;
+    let endpoint = format!(
+        "https://{}.tts.speech.microsoft.com/cognitiveservices/v1",
+        service_region.as_string()
+    );
+    let response = https_client
+        .post(endpoint)
+        .header("Authorization", bearer_token)
+        .header("X-Microsoft-OutputFormat", audio_format.as_string())
+        .header("Content-Type", "application/ssml+xml")
+        .header("User-Agent", "rust-azure-tts-client-lib")
+        .body(ssml)
+        .send()
+        .await?;
+    match response.status() {
+        reqwest::StatusCode::OK => (),
+        reqwest::StatusCode::BAD_REQUEST => return Err(TtsError::BadRequest),
+        reqwest::StatusCode::UNAUTHORIZED => return Err(TtsError::AuthError),
+        reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE => return Err(TtsError::UnsupportedMediaType),
+        reqwest::StatusCode::TOO_MANY_REQUESTS => return Err(TtsError::TooManyRequest),
+        _ => return Err(TtsError::UnknownConnectionError),
+    }
+    let audio = response.bytes().await?;
+    Ok(audio.chunk().to_vec())
 }
@@ -0,0 +1,256 @@
+/// BCP-47 language tags such as `en-US` or `zh-Hans-CN`.
+use crate::TtsError;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// Validated language tag in canonical case, `en-us` and `en_US` both become `en-US`
+///
+/// ```
+/// use azure_tts::Locale;
+///
+/// let locale: Locale = "zh_hans_cn".parse().unwrap();
+/// assert_eq!("zh-Hans-CN", locale.as_str());
+/// assert_eq!("zh", locale.language());
+/// assert_eq!(Some("Hans"), locale.script());
+/// assert_eq!(Some("CN"), locale.region());
+/// assert!("english".parse::<Locale>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Locale(String);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Subtag {
+    Language,
+    Script,
+    Region,
+    Other,
+}
+
+/// Kind of the subtag at `bytes[start..end]` following a subtag of kind `previous`
+const fn classify(
+    bytes: &[u8],
+    start: usize,
+    end: usize,
+    previous: Option<Subtag>,
+) -> Result<Subtag, &'static str> {
+    let length = end - start;
+    let mut alphabetic = true;
+    let mut numeric = true;
+    let mut index = start;
+    while index < end {
+        let byte = bytes[index];
+        if !byte.is_ascii_alphabetic() {
+            alphabetic = false;
+        }
+        if !byte.is_ascii_digit() {
+            numeric = false;
+        }
+        if !byte.is_ascii_alphanumeric() {
+            return Err("subtags may only contain ascii letters and digits");
+        }
+        index += 1;
+    }
+    let is_region = (alphabetic && length == 2) || (numeric && length == 3);
+    match previous {
+        None if alphabetic && (length == 2 || length == 3) => Ok(Subtag::Language),
+        None => Err("language must be 2 or 3 letters"),
+        Some(Subtag::Language) if alphabetic && length == 4 => Ok(Subtag::Script),
+        Some(Subtag::Language) | Some(Subtag::Script) if is_region => Ok(Subtag::Region),
+        _ if length >= 1 && length <= 8 => Ok(Subtag::Other),
+        _ => Err("subtags must be 1 to 8 characters long"),
+    }
+}
+
+/// Check the structure of a tag, usable in constant expressions
+pub(crate) const fn validate(tag: &str) -> Result<(), &'static str> {
+    let bytes = tag.as_bytes();
+    let mut previous = None;
+    let mut start = 0;
+    let mut end = 0;
+    while end <= bytes.len() {
+        if end == bytes.len() || bytes[end] == b'-' || bytes[end] == b'_' {
+            if end == start {
+                return Err("empty subtag");
+            }
+            previous = match classify(bytes, start, end, previous) {
+                Ok(subtag) => Some(subtag),
+                Err(error) => return Err(error),
+            };
+            start = end + 1;
+        }
+        end += 1;
+    }
+    Ok(())
+}
+
+impl Locale {
+    pub(crate) fn en_us() -> Self {
+        Self("en-US".to_owned())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn subtags(&self) -> impl Iterator<Item = (Subtag, &str)> {
+        let bytes = self.0.as_bytes();
+        let mut previous = None;
+        let mut start = 0;
+        self.0.split('-').map(move |subtag| {
+            let end = start + subtag.len();
+            let kind = classify(bytes, start, end, previous).unwrap_or(Subtag::Other);
+            previous = Some(kind);
+            start = end + 1;
+            (kind, subtag)
+        })
+    }
+
+    fn subtag(&self, kind: Subtag) -> Option<&str> {
+        self.subtags()
+            .find(|(subtag_kind, _)| *subtag_kind == kind)
+            .map(|(_, subtag)| subtag)
+    }
+
+    /// Primary language subtag such as `en`
+    pub fn language(&self) -> &str {
+        self.subtag(Subtag::Language).unwrap_or_default()
+    }
+
+    pub fn script(&self) -> Option<&str> {
+        self.subtag(Subtag::Script)
+    }
+
+    /// Region subtag such as `US` or `419`
+    pub fn region(&self) -> Option<&str> {
+        self.subtag(Subtag::Region)
+    }
+
+    /// Whether `self`, such as `en`, is `other` or a less specific form of it, such as `en-US`
+    pub fn includes(&self, other: &Locale) -> bool {
+        other.0 == self.0
+            || other
+                .0
+                .strip_prefix(&self.0)
+                .is_some_and(|rest| rest.starts_with('-'))
+    }
+}
+
+impl FromStr for Locale {
+    type Err = TtsError;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        validate(value).map_err(|error| {
+            TtsError::InvalidValue(format!("invalid locale {value:?}: {error}"))
+        })?;
+        let bytes = value.as_bytes();
+        let mut previous = None;
+        let mut start = 0;
+        let mut canonical = String::with_capacity(value.len());
+        for subtag in value.split(['-', '_']) {
+            let end = start + subtag.len();
+            let kind = classify(bytes, start, end, previous).unwrap_or(Subtag::Other);
+            if !canonical.is_empty() {
+                canonical.push('-');
+            }
+            match kind {
+                Subtag::Script => {
+                    canonical.push_str(&subtag[..1].to_ascii_uppercase());
+                    canonical.push_str(&subtag[1..].to_ascii_lowercase());
+                }
+                Subtag::Region => canonical.push_str(&subtag.to_ascii_uppercase()),
+                Subtag::Language | Subtag::Other => {
+                    canonical.push_str(&subtag.to_ascii_lowercase())
+                }
+            }
+            previous = Some(kind);
+            start = end + 1;
+        }
+        Ok(Self(canonical))
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Locale {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Case insensitive comparison with a tag
+impl PartialEq<str> for Locale {
+    fn eq(&self, other: &str) -> bool {
+        self.0.eq_ignore_ascii_case(&other.replace('_', "-"))
+    }
+}
+
+impl PartialEq<&str> for Locale {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Serialize for Locale {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Locale {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonicalizes_case_and_separators() {
+        let locale: Locale = "EN_us".parse().unwrap();
+        assert_eq!("en-US", locale.as_str());
+        assert_eq!("en", locale.language());
+        assert_eq!(None, locale.script());
+        assert_eq!(Some("US"), locale.region());
+        assert_eq!(locale, "en-us");
+
+        let locale: Locale = "es-419".parse().unwrap();
+        assert_eq!(Some("419"), locale.region());
+        let locale: Locale = "SR-latn-rs-VARIANT1".parse().unwrap();
+        assert_eq!("sr-Latn-RS-variant1", locale.as_str());
+        let locale: Locale = "wuu".parse().unwrap();
+        assert_eq!(("wuu", None), (locale.language(), locale.region()));
+    }
+
+    #[test]
+    fn rejects_invalid_tags() {
+        for tag in [
+            "",
+            "e",
+            "english-US",
+            "en--US",
+            "en-",
+            "en-US-toolongsubtag",
+            "en US",
+            "Jenny",
+        ] {
+            assert!(tag.parse::<Locale>().is_err(), "{tag}");
+        }
+    }
+
+    #[test]
+    fn includes_more_specific_tags() {
+        let english: Locale = "en".parse().unwrap();
+        let american: Locale = "en-US".parse().unwrap();
+        assert!(english.includes(&american));
+        assert!(american.includes(&american));
+        assert!(!american.includes(&english));
+        assert!(!english.includes(&"eng".parse().unwrap()));
+    }
+}
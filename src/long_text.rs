@@ -0,0 +1,143 @@
+/// Splitting of long text into chunks synthesized by separate requests.
+use crate::SentenceSegmenter;
+
+/// Default chunk size, roughly three minutes of speech
+const DEFAULT_MAX_CHUNK_SIZE: usize = 3000;
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Settings for [`crate::VoiceService::synthesize_long`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongTextSettings {
+    /// Maximum size of the text of a single request in bytes
+    pub max_chunk_size: usize,
+    pub max_concurrent_requests: usize,
+}
+
+impl Default for LongTextSettings {
+    fn default() -> Self {
+        Self {
+            max_chunk_size: DEFAULT_MAX_CHUNK_SIZE,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+        }
+    }
+}
+
+impl LongTextSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+        self.max_chunk_size = max_chunk_size.max(1);
+        self
+    }
+
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests.max(1);
+        self
+    }
+}
+
+/// Split text into chunks of whole sentences no longer than `max_size` bytes
+///
+/// Sentences that don't fit on their own are split between words, words between characters.
+pub(crate) fn split_chunks(
+    text: &str,
+    max_size: usize,
+    segmenter: &SentenceSegmenter,
+) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut chunk = String::new();
+    for sentence in segmenter.sentences(text) {
+        for piece in split_oversized(sentence, max_size) {
+            if !chunk.is_empty() && chunk.len() + 1 + piece.len() > max_size {
+                chunks.push(std::mem::take(&mut chunk));
+            }
+            if !chunk.is_empty() {
+                chunk.push(' ');
+            }
+            chunk.push_str(piece);
+        }
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Break a sentence longer than `max_size` at whitespace, or anywhere as a last resort
+fn split_oversized(sentence: &str, max_size: usize) -> Vec<&str> {
+    let mut pieces = vec![];
+    let mut rest = sentence.trim();
+    while rest.len() > max_size {
+        let mut end = max_size;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let split = if rest[end..].starts_with(char::is_whitespace) {
+            end
+        } else {
+            match rest[..end].rfind(char::is_whitespace) {
+                Some(space) if space > 0 => space,
+                // single character wider than the limit
+                _ if end == 0 => rest.chars().next().map(char::len_utf8).unwrap_or(1),
+                _ => end,
+            }
+        };
+        pieces.push(rest[..split].trim_end());
+        rest = rest[split..].trim_start();
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Locale;
+
+    #[test]
+    fn chunks_follow_sentences() {
+        let text = "First sentence. Second one! Third?\n\nFourth sentence here.";
+        assert_eq!(
+            vec![
+                "First sentence. Second one!".to_owned(),
+                "Third? Fourth sentence here.".to_owned(),
+            ],
+            split_chunks(text, 30, &SentenceSegmenter::default())
+        );
+    }
+
+    #[test]
+    fn chunks_keep_abbreviations() {
+        let segmenter = SentenceSegmenter::for_language(&Locale::en_us());
+        assert_eq!(
+            vec!["Dr. Who is here.".to_owned(), "Mr. Bean too.".to_owned()],
+            split_chunks("Dr. Who is here. Mr. Bean too.", 20, &segmenter)
+        );
+    }
+
+    #[test]
+    fn long_sentences_are_split_between_words() {
+        assert_eq!(
+            vec![
+                "one two".to_owned(),
+                "three four".to_owned(),
+                "five".to_owned()
+            ],
+            split_chunks("one two three four five", 10, &SentenceSegmenter::default())
+        );
+        assert_eq!(
+            vec!["abc".to_owned(), "def".to_owned(), "gh".to_owned()],
+            split_chunks("abcdefgh", 3, &SentenceSegmenter::default())
+        );
+    }
+
+    #[test]
+    fn chunks_respect_char_boundaries() {
+        let chunks = split_chunks("žžžž", 3, &SentenceSegmenter::default());
+        assert_eq!(vec!["ž".to_owned(); 4], chunks);
+    }
+}
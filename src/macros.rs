@@ -0,0 +1,252 @@
+/// Compile time checked SSML markup.
+///
+/// Voices are [`crate::EnUsVoices`] variants and styles, roles, emphasis levels and break strengths
+/// are variant names of their respective enums. Break times, style degrees and languages are checked at compile time.
+///
+/// ```
+/// use azure_tts::ssml;
+///
+/// let speak = ssml!(voice(JennyNeural) {
+///     "Hello"
+///     break(500ms)
+///     express(Cheerful, degree = 1.5, role = Girl) { "world" }
+///     emphasis(Strong) { "!" }
+/// });
+/// ```
+///
+/// Invalid values are rejected by the compiler:
+///
+/// ```compile_fail
+/// let speak = azure_tts::ssml!(voice(JennyNeural) { break(10s) });
+/// ```
+///
+/// ```compile_fail
+/// let speak = azure_tts::ssml!(voice(JennyNeural) { express(Overjoyed) { "Hi" } });
+/// ```
+///
+/// ```compile_fail
+/// let speak = azure_tts::ssml!(voice(JennyNeural) { lang("German") { "Hallo" } });
+/// ```
+#[macro_export]
+macro_rules! ssml {
+    ($(voice($voice:ident) { $($body:tt)* })+) => {
+        $crate::Speak::voices_with_voice_settings(vec![
+            $((
+                $crate::EnUsVoices::$voice.to_voice_settings(),
+                $crate::__ssml_segments!($($body)*),
+            ),)+
+        ])
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ssml_segments {
+    (@acc [$($out:expr,)*]) => {
+        vec![$($out,)*]
+    };
+    (@acc [$($out:expr,)*] $text:literal $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)* $crate::VoiceSegment::plain($text),] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] break($strength:ident) $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            $crate::VoiceSegment::pause_with_strength($crate::BreakStrength::$strength),
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] break($time:tt) $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            {
+                const MILLIS: u64 = $crate::__private::break_millis(stringify!($time));
+                $crate::VoiceSegment::pause(::std::time::Duration::from_millis(MILLIS))
+                    .expect("break time checked at compile time")
+            },
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*]
+        express($style:ident $(, degree = $degree:literal)? $(, role = $role:ident)?) { $($body:tt)* }
+        $($rest:tt)*
+    ) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            $crate::VoiceSegment::express_as(
+                $crate::Expression::new($crate::Style::$style)
+                    $(.with_style_degree({
+                        const DEGREE: f32 = $crate::__private::style_degree($degree);
+                        DEGREE
+                    }).expect("style degree checked at compile time"))?
+                    $(.with_role($crate::Role::$role))?,
+                $crate::__ssml_segments!($($body)*),
+            ),
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] emphasis($level:ident) { $($body:tt)* } $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            $crate::VoiceSegment::emphasis(
+                $crate::EmphasisLevel::$level,
+                $crate::__ssml_segments!($($body)*),
+            ),
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] lang($language:literal) { $($body:tt)* } $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            {
+                const LANGUAGE: &str = $crate::__private::locale($language);
+                $crate::VoiceSegment::lang(
+                    &LANGUAGE.parse().expect("language checked at compile time"),
+                    $crate::__ssml_segments!($($body)*),
+                )
+            },
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] p { $($body:tt)* } $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            $crate::VoiceSegment::paragraph($crate::__ssml_segments!($($body)*)),
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] s { $($body:tt)* } $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            $crate::VoiceSegment::sentence($crate::__ssml_segments!($($body)*)),
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] sub($text:literal, $alias:literal) $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            $crate::VoiceSegment::sub($text, $alias),
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] say_as($interpret_as:ident, $text:literal) $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            $crate::VoiceSegment::say_as($text, $crate::InterpretAs::$interpret_as, None),
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] bookmark($mark:literal) $($rest:tt)*) => {
+        $crate::__ssml_segments!(@acc [$($out,)*
+            $crate::VoiceSegment::bookmark($mark),
+        ] $($rest)*)
+    };
+    (@acc [$($out:expr,)*] $unexpected:tt $($rest:tt)*) => {
+        compile_error!(concat!("unexpected ssml markup: ", stringify!($unexpected)))
+    };
+    ($($markup:tt)*) => {
+        $crate::__ssml_segments!(@acc [] $($markup)*)
+    };
+}
+
+/// Helpers evaluated in const context by the macros
+#[doc(hidden)]
+pub mod __private {
+    use crate::{
+        locale::validate,
+        ssml_serializer::{MAX_BREAK, MAX_STYLE_DEGREE, MIN_STYLE_DEGREE},
+    };
+
+    /// Parse break time such as `500ms` or `2s` into milliseconds
+    pub const fn break_millis(time: &str) -> u64 {
+        let bytes = time.as_bytes();
+        let mut index = 0;
+        let mut value: u64 = 0;
+        while index < bytes.len() && bytes[index].is_ascii_digit() {
+            value = value * 10 + (bytes[index] - b'0') as u64;
+            index += 1;
+        }
+        if index == 0 {
+            panic!("break time must start with a number");
+        }
+        let unit = bytes.len() - index;
+        let millis = if unit == 2 && bytes[index] == b'm' && bytes[index + 1] == b's' {
+            value
+        } else if unit == 1 && bytes[index] == b's' {
+            value * 1000
+        } else {
+            panic!("break time must be in ms or s");
+        };
+        if millis as u128 > MAX_BREAK.as_millis() {
+            panic!("break time can't be longer than 5000ms");
+        }
+        millis
+    }
+
+    pub const fn locale(tag: &str) -> &str {
+        if validate(tag).is_err() {
+            panic!("language must be a BCP-47 tag such as en-US");
+        }
+        tag
+    }
+
+    pub const fn style_degree(degree: f32) -> f32 {
+        if degree < MIN_STYLE_DEGREE || degree > MAX_STYLE_DEGREE {
+            panic!("style degree has to be between 0.01 and 2.0");
+        }
+        degree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        EmphasisLevel, EnUsVoices, Expression, InterpretAs, Role, Speak, Style, VoiceSegment,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn expands_nested_markup() {
+        let speak = ssml!(voice(JennyNeural) {
+            "Hello "
+            break(500ms)
+            express(Cheerful, degree = 1.5, role = Girl) {
+                "great "
+                emphasis(Strong) { "news" }
+            }
+            say_as(Date, "2022-01-01")
+        });
+
+        let expected = Speak::segments_with_voice_settings(
+            &EnUsVoices::JennyNeural.to_voice_settings(),
+            vec![
+                VoiceSegment::plain("Hello "),
+                VoiceSegment::pause(Duration::from_millis(500)).unwrap(),
+                VoiceSegment::express_as(
+                    Expression::new(Style::Cheerful)
+                        .with_style_degree(1.5)
+                        .unwrap()
+                        .with_role(Role::Girl),
+                    vec![
+                        VoiceSegment::plain("great "),
+                        VoiceSegment::emphasis(
+                            EmphasisLevel::Strong,
+                            vec![VoiceSegment::plain("news")],
+                        ),
+                    ],
+                ),
+                VoiceSegment::say_as("2022-01-01", InterpretAs::Date, None),
+            ],
+        );
+        assert_eq!(expected, speak);
+    }
+
+    #[test]
+    fn expands_multiple_voices() {
+        let speak = ssml!(
+            voice(JennyNeural) { p { s { "Hi" } } break(2s) }
+            voice(GuyNeural) { sub("BTW", "by the way") bookmark("end") }
+        );
+
+        let expected = Speak::voices_with_voice_settings(vec![
+            (
+                EnUsVoices::JennyNeural.to_voice_settings(),
+                vec![
+                    VoiceSegment::paragraph(vec![VoiceSegment::sentence(vec![
+                        VoiceSegment::plain("Hi"),
+                    ])]),
+                    VoiceSegment::pause(Duration::from_secs(2)).unwrap(),
+                ],
+            ),
+            (
+                EnUsVoices::GuyNeural.to_voice_settings(),
+                vec![
+                    VoiceSegment::sub("BTW", "by the way"),
+                    VoiceSegment::bookmark("end"),
+                ],
+            ),
+        ]);
+        assert_eq!(expected, speak);
+    }
+}
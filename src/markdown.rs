@@ -0,0 +1,343 @@
+/// Markdown to ssml conversion for narrating documents.
+///
+/// Headings are emphasized and followed by a pause, list items and table rows become sentences,
+/// links are read as their text and code blocks, images and html are skipped.
+use crate::{
+    ssml_parser::push_text, ssml_serializer::VoiceSegment, BreakStrength, EmphasisLevel,
+    InterpretAs, Speak, VoiceSettings,
+};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag};
+
+/// How inline code is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeSpans {
+    /// Read character by character
+    SpellOut,
+    /// Read as ordinary text
+    Read,
+    Skip,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarkdownConverter {
+    code_spans: CodeSpans,
+}
+
+impl Default for MarkdownConverter {
+    fn default() -> Self {
+        Self {
+            code_spans: CodeSpans::SpellOut,
+        }
+    }
+}
+
+impl Speak {
+    /// Convert markdown using the default [`MarkdownConverter`]
+    pub fn from_markdown(markdown: &str, voice_settings: &VoiceSettings) -> Speak {
+        MarkdownConverter::new().convert(markdown, voice_settings)
+    }
+}
+
+enum Frame {
+    Root,
+    Paragraph,
+    Heading(HeadingLevel),
+    List(Option<u64>),
+    Item(Option<u64>),
+    Table,
+    /// Header or body row of a table
+    Row,
+    Emphasis(EmphasisLevel),
+    /// Content dropped from the output
+    Skip,
+    /// Content passed to the parent as is
+    Transparent,
+    /// Content passed to the parent after a separator if the parent already has content
+    Separated(&'static str),
+}
+
+struct Level {
+    frame: Frame,
+    segments: Vec<VoiceSegment>,
+    /// Segments of nested lists, placed after the item sentence
+    trailing: Vec<VoiceSegment>,
+}
+
+impl MarkdownConverter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_code_spans(mut self, code_spans: CodeSpans) -> Self {
+        self.code_spans = code_spans;
+        self
+    }
+
+    pub fn convert(&self, markdown: &str, voice_settings: &VoiceSettings) -> Speak {
+        Speak::segments_with_voice_settings(voice_settings, self.to_segments(markdown))
+    }
+
+    pub fn to_segments(&self, markdown: &str) -> Vec<VoiceSegment> {
+        let mut stack = vec![Level::new(Frame::Root)];
+        for event in Parser::new_ext(markdown, Options::ENABLE_TABLES) {
+            let skipping = stack.iter().any(|level| matches!(level.frame, Frame::Skip));
+            match event {
+                Event::Start(tag) => {
+                    let frame = Self::frame(tag, &mut stack, skipping);
+                    stack.push(Level::new(frame));
+                }
+                Event::End(_) => {
+                    let level = stack.pop().expect("end without start");
+                    let parent = stack.last_mut().expect("root frame closed");
+                    level.finish(parent);
+                }
+                _ if skipping => (),
+                Event::Text(text) => push_text(current(&mut stack), text.to_string()),
+                Event::Code(code) => match self.code_spans {
+                    CodeSpans::SpellOut => current(&mut stack).push(VoiceSegment::say_as(
+                        &code,
+                        InterpretAs::Characters,
+                        None,
+                    )),
+                    CodeSpans::Read => push_text(current(&mut stack), code.to_string()),
+                    CodeSpans::Skip => (),
+                },
+                Event::SoftBreak | Event::HardBreak => {
+                    push_text(current(&mut stack), " ".to_owned())
+                }
+                Event::Rule => current(&mut stack)
+                    .push(VoiceSegment::pause_with_strength(BreakStrength::XStrong)),
+                _ => (),
+            }
+        }
+        stack.pop().map(|root| root.segments).unwrap_or_default()
+    }
+
+    fn frame(tag: Tag, stack: &mut [Level], skipping: bool) -> Frame {
+        if skipping {
+            return Frame::Skip;
+        }
+        let in_item = stack
+            .iter()
+            .any(|level| matches!(level.frame, Frame::Item(_)));
+        match tag {
+            // p isn't allowed inside of the sentence of a list item
+            Tag::Paragraph if in_item => Frame::Separated(" "),
+            Tag::Paragraph => Frame::Paragraph,
+            Tag::Heading(level, ..) => Frame::Heading(level),
+            Tag::List(start) => Frame::List(start),
+            Tag::Item => {
+                let number = match stack.last_mut().map(|level| &mut level.frame) {
+                    Some(Frame::List(Some(next))) => {
+                        let number = *next;
+                        *next += 1;
+                        Some(number)
+                    }
+                    _ => None,
+                };
+                Frame::Item(number)
+            }
+            Tag::Table(_) => Frame::Table,
+            Tag::TableHead | Tag::TableRow => Frame::Row,
+            Tag::TableCell => Frame::Separated(", "),
+            Tag::Emphasis => Frame::Emphasis(EmphasisLevel::Moderate),
+            Tag::Strong => Frame::Emphasis(EmphasisLevel::Strong),
+            Tag::CodeBlock(_) | Tag::Image(..) | Tag::FootnoteDefinition(_) => Frame::Skip,
+            _ => Frame::Transparent,
+        }
+    }
+}
+
+impl Level {
+    fn new(frame: Frame) -> Self {
+        Self {
+            frame,
+            segments: vec![],
+            trailing: vec![],
+        }
+    }
+
+    fn finish(self, parent: &mut Level) {
+        let Level {
+            frame,
+            mut segments,
+            trailing,
+        } = self;
+        match frame {
+            Frame::Root | Frame::Skip => (),
+            Frame::Paragraph if segments.is_empty() => (),
+            Frame::Paragraph => parent.segments.push(VoiceSegment::paragraph(segments)),
+            Frame::Heading(level) => {
+                let emphasis = match level {
+                    HeadingLevel::H1 | HeadingLevel::H2 => EmphasisLevel::Strong,
+                    _ => EmphasisLevel::Moderate,
+                };
+                parent
+                    .segments
+                    .push(VoiceSegment::emphasis(emphasis, segments));
+                parent
+                    .segments
+                    .push(VoiceSegment::pause_with_strength(BreakStrength::Strong));
+            }
+            Frame::List(_) | Frame::Table => {
+                if matches!(parent.frame, Frame::Item(_)) {
+                    parent.trailing.extend(segments);
+                } else {
+                    parent.segments.extend(segments);
+                }
+            }
+            Frame::Item(number) => {
+                if let Some(number) = number {
+                    let prefix = format!("{}. ", number);
+                    match segments.first_mut() {
+                        Some(VoiceSegment::Plain(text)) => text.insert_str(0, &prefix),
+                        _ => segments.insert(0, VoiceSegment::plain(&prefix)),
+                    }
+                }
+                if !segments.is_empty() {
+                    parent.segments.push(VoiceSegment::sentence(segments));
+                }
+                parent.segments.extend(trailing);
+            }
+            Frame::Row if segments.is_empty() => (),
+            Frame::Row => parent.segments.push(VoiceSegment::sentence(segments)),
+            Frame::Emphasis(level) => parent
+                .segments
+                .push(VoiceSegment::emphasis(level, segments)),
+            Frame::Separated(separator) => {
+                if !segments.is_empty() && !parent.segments.is_empty() {
+                    push_text(&mut parent.segments, separator.to_owned());
+                }
+                let level = Level {
+                    frame: Frame::Transparent,
+                    segments,
+                    trailing,
+                };
+                level.finish(parent);
+            }
+            Frame::Transparent => {
+                for segment in segments {
+                    match segment {
+                        VoiceSegment::Plain(text) => push_text(&mut parent.segments, text),
+                        segment => parent.segments.push(segment),
+                    }
+                }
+                parent.trailing.extend(trailing);
+            }
+        }
+    }
+}
+
+fn current(stack: &mut [Level]) -> &mut Vec<VoiceSegment> {
+    &mut stack.last_mut().expect("root frame closed").segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnUsVoices;
+
+    #[test]
+    fn converts_document() {
+        let markdown = "# Release notes
+
+Adds *fluent* builder. See [the docs](https://example.com) for `ssml!`.
+
+1. First
+2. Second
+   - nested
+
+```rust
+let skipped = true;
+```
+";
+        let speak = Speak::from_markdown(markdown, &EnUsVoices::JennyNeural.to_voice_settings());
+        let expected = Speak::segments_with_voice_settings(
+            &EnUsVoices::JennyNeural.to_voice_settings(),
+            vec![
+                VoiceSegment::emphasis(
+                    EmphasisLevel::Strong,
+                    vec![VoiceSegment::plain("Release notes")],
+                ),
+                VoiceSegment::pause_with_strength(BreakStrength::Strong),
+                VoiceSegment::paragraph(vec![
+                    VoiceSegment::plain("Adds "),
+                    VoiceSegment::emphasis(
+                        EmphasisLevel::Moderate,
+                        vec![VoiceSegment::plain("fluent")],
+                    ),
+                    VoiceSegment::plain(" builder. See the docs for "),
+                    VoiceSegment::say_as("ssml!", InterpretAs::Characters, None),
+                    VoiceSegment::plain("."),
+                ]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("1. First")]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("2. Second")]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("nested")]),
+            ],
+        );
+        assert_eq!(expected, speak);
+        speak.validate().unwrap();
+    }
+
+    #[test]
+    fn loose_list_item_paragraphs() {
+        assert_eq!(
+            vec![
+                VoiceSegment::sentence(vec![VoiceSegment::plain("First para. Second para.")]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("Next item.")]),
+            ],
+            MarkdownConverter::new().to_segments(
+                "- First para.
+
+  Second para.
+
+- Next item.
+"
+            )
+        );
+    }
+
+    #[test]
+    fn reads_tables_by_row() {
+        let markdown = "Changes:
+
+| Feature | Status |
+|---|---|
+| Tables | *done* |
+| Images |  |
+";
+        assert_eq!(
+            vec![
+                VoiceSegment::paragraph(vec![VoiceSegment::plain("Changes:")]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("Feature, Status")]),
+                VoiceSegment::sentence(vec![
+                    VoiceSegment::plain("Tables, "),
+                    VoiceSegment::emphasis(
+                        EmphasisLevel::Moderate,
+                        vec![VoiceSegment::plain("done")]
+                    ),
+                ]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("Images")]),
+            ],
+            MarkdownConverter::new().to_segments(markdown)
+        );
+    }
+
+    #[test]
+    fn code_spans() {
+        let converter = MarkdownConverter::new().with_code_spans(CodeSpans::Skip);
+        assert_eq!(
+            vec![VoiceSegment::paragraph(vec![VoiceSegment::plain(
+                "Run  now"
+            )])],
+            converter.to_segments("Run `cargo test` now")
+        );
+        let converter = MarkdownConverter::new().with_code_spans(CodeSpans::Read);
+        assert_eq!(
+            vec![VoiceSegment::paragraph(vec![VoiceSegment::plain(
+                "Run cargo test now"
+            )])],
+            converter.to_segments("Run `cargo test` now")
+        );
+    }
+}
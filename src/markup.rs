@@ -0,0 +1,356 @@
+/// Lightweight inline markup for authoring speech without writing xml.
+///
+/// | Markup                          | Result                                 |
+/// |---------------------------------|----------------------------------------|
+/// | `*text*`                        | moderate emphasis                      |
+/// | `**text**`                      | strong emphasis                        |
+/// | `{cheerful: text}`              | express-as with the given style        |
+/// | `[pause 500ms]`, `[pause weak]` | break with time or strength            |
+/// | `[say-as date: 2022-01-01]`     | say-as, optionally `format=dmy`        |
+/// | `[sub by the way: BTW]`         | text read as the alias                 |
+/// | `[bookmark name]`               | bookmark                               |
+///
+/// Use `\` to escape any of `*{}[]\`.
+use crate::{
+    ssml_parser::parse_time, ssml_serializer::VoiceSegment, xml::line_column, BreakStrength,
+    EmphasisLevel, InterpretAs, Result, Style, TtsError,
+};
+
+impl VoiceSegment {
+    /// Parse inline markup into segments ready for [`crate::VoiceService::synthesize_segments`]
+    ///
+    /// ```
+    /// use azure_tts::VoiceSegment;
+    ///
+    /// let segments =
+    ///     VoiceSegment::from_markup_str("Hello [pause 500ms] *world* {cheerful: great news}").unwrap();
+    /// assert_eq!(6, segments.len());
+    /// ```
+    pub fn from_markup_str(markup: &str) -> Result<Vec<VoiceSegment>> {
+        MarkupParser {
+            source: markup,
+            position: 0,
+        }
+        .parse_content(None)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delimiter {
+    Emphasis,
+    StrongEmphasis,
+    Expression,
+}
+
+impl Delimiter {
+    fn as_string(&self) -> &'static str {
+        match self {
+            Delimiter::Emphasis => "*",
+            Delimiter::StrongEmphasis => "**",
+            Delimiter::Expression => "}",
+        }
+    }
+}
+
+struct MarkupParser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> MarkupParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn error(&self, position: usize, message: impl ToString) -> TtsError {
+        let (line, column) = line_column(self.source, position);
+        TtsError::MarkupParseError {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    /// Parse until `closing` or the end of input when `closing` is None
+    fn parse_content(&mut self, closing: Option<(Delimiter, usize)>) -> Result<Vec<VoiceSegment>> {
+        let mut segments = vec![];
+        let mut text = String::new();
+        while let Some(character) = self.rest().chars().next() {
+            let start = self.position;
+            match character {
+                '\\' => {
+                    self.position += 1;
+                    let escaped =
+                        self.rest().chars().next().ok_or_else(|| {
+                            self.error(start, "nothing to escape at end of input")
+                        })?;
+                    text.push(escaped);
+                    self.position += escaped.len_utf8();
+                    continue;
+                }
+                '*' => {
+                    let delimiter = if self.rest().starts_with("**") {
+                        Delimiter::StrongEmphasis
+                    } else {
+                        Delimiter::Emphasis
+                    };
+                    self.position += delimiter.as_string().len();
+                    if matches!(closing, Some((closing, _)) if closing == delimiter) {
+                        flush_text(&mut segments, &mut text);
+                        return Ok(segments);
+                    }
+                    let level = match delimiter {
+                        Delimiter::StrongEmphasis => EmphasisLevel::Strong,
+                        _ => EmphasisLevel::Moderate,
+                    };
+                    let contents = self.parse_content(Some((delimiter, start)))?;
+                    flush_text(&mut segments, &mut text);
+                    segments.push(VoiceSegment::emphasis(level, contents));
+                }
+                '{' => {
+                    self.position += 1;
+                    let segment = self.parse_expression(start)?;
+                    flush_text(&mut segments, &mut text);
+                    segments.push(segment);
+                }
+                '}' if matches!(closing, Some((Delimiter::Expression, _))) => {
+                    self.position += 1;
+                    flush_text(&mut segments, &mut text);
+                    return Ok(segments);
+                }
+                '[' => {
+                    self.position += 1;
+                    let segment = self.parse_command(start)?;
+                    flush_text(&mut segments, &mut text);
+                    segments.push(segment);
+                }
+                '}' | ']' => {
+                    return Err(self.error(
+                        start,
+                        format!(
+                            "unexpected {:?}, use \\{} to write it as text",
+                            character, character
+                        ),
+                    ))
+                }
+                _ => {
+                    text.push(character);
+                    self.position += character.len_utf8();
+                }
+            }
+        }
+        if let Some((delimiter, opening)) = closing {
+            return Err(self.error(
+                opening,
+                format!("missing closing {:?}", delimiter.as_string()),
+            ));
+        }
+        flush_text(&mut segments, &mut text);
+        Ok(segments)
+    }
+
+    /// `{style: content}`, called after the opening brace
+    fn parse_expression(&mut self, opening: usize) -> Result<VoiceSegment> {
+        let colon = self
+            .rest()
+            .find(':')
+            .ok_or_else(|| self.error(opening, "expected \"{style: text}\""))?;
+        let name = self.rest()[..colon].trim();
+        if name.is_empty() || name.contains(|c: char| c.is_whitespace() || "{}[]*".contains(c)) {
+            return Err(self.error(opening, "expected \"{style: text}\""));
+        }
+        let style: Style = name.parse().unwrap_or_else(|never| match never {});
+        if let Style::Other(style) = style {
+            return Err(self.error(opening, format!("unknown style {:?}", style)));
+        }
+        self.position += colon + 1;
+        let contents = self.parse_content(Some((Delimiter::Expression, opening)))?;
+        Ok(VoiceSegment::express_as(style, trim_segments(contents)))
+    }
+
+    /// `[name arguments]`, called after the opening bracket
+    fn parse_command(&mut self, opening: usize) -> Result<VoiceSegment> {
+        let end = self
+            .rest()
+            .find([']', '\n'])
+            .filter(|end| self.rest()[*end..].starts_with(']'))
+            .ok_or_else(|| self.error(opening, "missing closing \"]\""))?;
+        let command = self.rest()[..end].trim();
+        self.position += end + 1;
+        let (name, arguments) = command
+            .split_once(char::is_whitespace)
+            .map(|(name, arguments)| (name, arguments.trim()))
+            .unwrap_or((command, ""));
+        match name {
+            "pause" => {
+                if let Ok(strength) = arguments.parse::<BreakStrength>() {
+                    Ok(VoiceSegment::pause_with_strength(strength))
+                } else {
+                    let time = parse_time(arguments).ok_or_else(|| {
+                        self.error(
+                            opening,
+                            format!("expected pause time or strength, found {:?}", arguments),
+                        )
+                    })?;
+                    VoiceSegment::pause(time).map_err(|error| self.error(opening, error))
+                }
+            }
+            "say-as" => {
+                let (options, text) = self.split_text(opening, name, arguments)?;
+                let mut options = options.split_whitespace();
+                let interpret_as: InterpretAs = options
+                    .next()
+                    .ok_or_else(|| self.error(opening, "say-as requires a type"))?
+                    .parse()
+                    .unwrap_or_else(|never| match never {});
+                if let InterpretAs::Other(value) = interpret_as {
+                    return Err(self.error(opening, format!("unknown say-as type {:?}", value)));
+                }
+                let mut format = None;
+                for option in options {
+                    match option.split_once('=') {
+                        Some(("format", value)) => format = Some(value),
+                        _ => {
+                            return Err(
+                                self.error(opening, format!("unknown say-as option {:?}", option))
+                            )
+                        }
+                    }
+                }
+                Ok(VoiceSegment::say_as(text, interpret_as, format))
+            }
+            "sub" => {
+                let (alias, text) = self.split_text(opening, name, arguments)?;
+                Ok(VoiceSegment::sub(text, alias))
+            }
+            "bookmark" if !arguments.is_empty() => Ok(VoiceSegment::bookmark(arguments)),
+            "bookmark" => Err(self.error(opening, "bookmark requires a name")),
+            _ => Err(self.error(opening, format!("unknown command {:?}", name))),
+        }
+    }
+
+    /// Split `options: text` arguments of a command
+    fn split_text<'b>(
+        &self,
+        opening: usize,
+        name: &str,
+        arguments: &'b str,
+    ) -> Result<(&'b str, &'b str)> {
+        match arguments.split_once(':') {
+            Some((options, text)) if !options.trim().is_empty() && !text.trim().is_empty() => {
+                Ok((options.trim(), text.trim()))
+            }
+            _ => Err(self.error(opening, format!("expected \"[{} ...: text]\"", name))),
+        }
+    }
+}
+
+fn flush_text(segments: &mut Vec<VoiceSegment>, text: &mut String) {
+    if !text.is_empty() {
+        segments.push(VoiceSegment::plain(text));
+        text.clear();
+    }
+}
+
+/// Drop whitespace following the colon of an expression
+fn trim_segments(mut segments: Vec<VoiceSegment>) -> Vec<VoiceSegment> {
+    if let Some(VoiceSegment::Plain(text)) = segments.first_mut() {
+        *text = text.trim_start().to_owned();
+        if text.is_empty() {
+            segments.remove(0);
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_markup() {
+        let segments =
+            VoiceSegment::from_markup_str("Hello [pause 500ms] *world* {cheerful: great **news**}")
+                .unwrap();
+        let expected = vec![
+            VoiceSegment::plain("Hello "),
+            VoiceSegment::pause(Duration::from_millis(500)).unwrap(),
+            VoiceSegment::plain(" "),
+            VoiceSegment::emphasis(EmphasisLevel::Moderate, vec![VoiceSegment::plain("world")]),
+            VoiceSegment::plain(" "),
+            VoiceSegment::express_as(
+                Style::Cheerful,
+                vec![
+                    VoiceSegment::plain("great "),
+                    VoiceSegment::emphasis(
+                        EmphasisLevel::Strong,
+                        vec![VoiceSegment::plain("news")],
+                    ),
+                ],
+            ),
+        ];
+        assert_eq!(expected, segments);
+    }
+
+    #[test]
+    fn parses_commands() {
+        let segments = VoiceSegment::from_markup_str(
+            "[say-as date format=dmy: 01-02-2022][sub by the way: BTW][pause strong][bookmark end] \\*",
+        )
+        .unwrap();
+        let expected = vec![
+            VoiceSegment::say_as("01-02-2022", InterpretAs::Date, Some("dmy")),
+            VoiceSegment::sub("BTW", "by the way"),
+            VoiceSegment::pause_with_strength(BreakStrength::Strong),
+            VoiceSegment::bookmark("end"),
+            VoiceSegment::plain(" *"),
+        ];
+        assert_eq!(expected, segments);
+    }
+
+    fn error_position(markup: &str) -> (usize, usize, String) {
+        match VoiceSegment::from_markup_str(markup) {
+            Err(TtsError::MarkupParseError {
+                line,
+                column,
+                message,
+            }) => (line, column, message),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(
+            (2, 7, "missing closing \"*\"".to_owned()),
+            error_position("Hello\nworld *unfinished")
+        );
+        assert_eq!(
+            (1, 1, "unknown style \"grumpy\"".to_owned()),
+            error_position("{grumpy: no}")
+        );
+        assert_eq!(
+            (1, 3, "unknown command \"wait\"".to_owned()),
+            error_position("a [wait 5s]")
+        );
+        assert_eq!(
+            (
+                1,
+                1,
+                "expected pause time or strength, found \"soon\"".to_owned()
+            ),
+            error_position("[pause soon]")
+        );
+        let (line, column, _) = error_position("[pause 10s]");
+        assert_eq!((1, 1), (line, column));
+        assert_eq!(
+            (
+                1,
+                5,
+                "unexpected '}', use \\} to write it as text".to_owned()
+            ),
+            error_position("oops} text")
+        );
+    }
+}
@@ -0,0 +1,716 @@
+/// Normalization of plain text before it is turned into ssml.
+///
+/// Expands abbreviations, units and currencies of a locale, applies user defined replacements,
+/// verbalizes urls and email addresses, strips or names emoji and can annotate numbers,
+/// dates and times with `say-as`.
+use crate::{ssml_serializer::VoiceSegment, InterpretAs, Locale, Speak, VoiceSettings};
+
+/// Words of a language used by the normalizer
+struct LocaleRules {
+    language: &'static str,
+    /// Replacements of whole words, matched case insensitively
+    abbreviations: &'static [(&'static str, &'static str)],
+    /// Units and currencies following a number, with singular and plural
+    units: &'static [(&'static str, &'static str, &'static str)],
+    /// Words for `@`, `.` and `/` in addresses
+    at: &'static str,
+    dot: &'static str,
+    slash: &'static str,
+    emoji_names: &'static [(char, &'static str)],
+}
+
+const ENGLISH: LocaleRules = LocaleRules {
+    language: "en",
+    abbreviations: &[
+        ("&", "and"),
+        ("approx.", "approximately"),
+        ("e.g.", "for example"),
+        ("etc.", "et cetera"),
+        ("i.e.", "that is"),
+        ("vs.", "versus"),
+    ],
+    units: &[
+        ("%", "percent", "percent"),
+        ("km", "kilometer", "kilometers"),
+        ("km/h", "kilometer per hour", "kilometers per hour"),
+        ("m", "meter", "meters"),
+        ("cm", "centimeter", "centimeters"),
+        ("mm", "millimeter", "millimeters"),
+        ("kg", "kilogram", "kilograms"),
+        ("g", "gram", "grams"),
+        ("mg", "milligram", "milligrams"),
+        ("ml", "milliliter", "milliliters"),
+        ("mph", "mile per hour", "miles per hour"),
+        ("°C", "degree Celsius", "degrees Celsius"),
+        ("°F", "degree Fahrenheit", "degrees Fahrenheit"),
+        ("$", "dollar", "dollars"),
+        ("€", "euro", "euros"),
+        ("£", "pound", "pounds"),
+        ("¥", "yen", "yen"),
+    ],
+    at: "at",
+    dot: "dot",
+    slash: "slash",
+    emoji_names: &[
+        ('😀', "grinning face"),
+        ('🙂', "smiling face"),
+        ('😂', "face with tears of joy"),
+        ('😢', "crying face"),
+        ('😉', "winking face"),
+        ('❤', "red heart"),
+        ('👍', "thumbs up"),
+        ('👎', "thumbs down"),
+        ('🎉', "party popper"),
+        ('🔥', "fire"),
+        ('✅', "check mark"),
+        ('🚀', "rocket"),
+    ],
+};
+
+const LOCALE_RULES: &[LocaleRules] = &[
+    ENGLISH,
+    LocaleRules {
+        language: "de",
+        abbreviations: &[
+            ("&", "und"),
+            ("bzw.", "beziehungsweise"),
+            ("ca.", "circa"),
+            ("d.h.", "das heißt"),
+            ("usw.", "und so weiter"),
+            ("z.B.", "zum Beispiel"),
+        ],
+        units: &[
+            ("%", "Prozent", "Prozent"),
+            ("km", "Kilometer", "Kilometer"),
+            ("km/h", "Kilometer pro Stunde", "Kilometer pro Stunde"),
+            ("m", "Meter", "Meter"),
+            ("cm", "Zentimeter", "Zentimeter"),
+            ("mm", "Millimeter", "Millimeter"),
+            ("kg", "Kilogramm", "Kilogramm"),
+            ("g", "Gramm", "Gramm"),
+            ("ml", "Milliliter", "Milliliter"),
+            ("°C", "Grad Celsius", "Grad Celsius"),
+            ("$", "Dollar", "Dollar"),
+            ("€", "Euro", "Euro"),
+            ("£", "Pfund", "Pfund"),
+        ],
+        at: "ät",
+        dot: "Punkt",
+        slash: "Schrägstrich",
+        emoji_names: &[],
+    },
+    LocaleRules {
+        language: "fr",
+        abbreviations: &[
+            ("&", "et"),
+            ("env.", "environ"),
+            ("etc.", "et cetera"),
+            ("p.ex.", "par exemple"),
+        ],
+        units: &[
+            ("%", "pour cent", "pour cent"),
+            ("km", "kilomètre", "kilomètres"),
+            ("km/h", "kilomètre par heure", "kilomètres par heure"),
+            ("m", "mètre", "mètres"),
+            ("cm", "centimètre", "centimètres"),
+            ("kg", "kilogramme", "kilogrammes"),
+            ("g", "gramme", "grammes"),
+            ("°C", "degré Celsius", "degrés Celsius"),
+            ("$", "dollar", "dollars"),
+            ("€", "euro", "euros"),
+            ("£", "livre", "livres"),
+        ],
+        at: "arobase",
+        dot: "point",
+        slash: "barre oblique",
+        emoji_names: &[],
+    },
+    LocaleRules {
+        language: "es",
+        abbreviations: &[
+            ("&", "y"),
+            ("aprox.", "aproximadamente"),
+            ("etc.", "etcétera"),
+            ("p.ej.", "por ejemplo"),
+        ],
+        units: &[
+            ("%", "por ciento", "por ciento"),
+            ("km", "kilómetro", "kilómetros"),
+            ("km/h", "kilómetro por hora", "kilómetros por hora"),
+            ("m", "metro", "metros"),
+            ("cm", "centímetro", "centímetros"),
+            ("kg", "kilogramo", "kilogramos"),
+            ("g", "gramo", "gramos"),
+            ("°C", "grado Celsius", "grados Celsius"),
+            ("$", "dólar", "dólares"),
+            ("€", "euro", "euros"),
+            ("£", "libra", "libras"),
+        ],
+        at: "arroba",
+        dot: "punto",
+        slash: "barra",
+        emoji_names: &[],
+    },
+];
+
+const CURRENCY_SYMBOLS: &[char] = &['$', '€', '£', '¥'];
+
+/// What happens to emoji in the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmojiHandling {
+    Keep,
+    Strip,
+    /// Read the name of known emoji, others are stripped. Names are only known for English
+    Name,
+}
+
+/// Configurable normalization of text for a locale
+///
+/// ```
+/// use azure_tts::TextNormalizer;
+///
+/// let normalizer = TextNormalizer::for_language(&"en-US".parse().unwrap())
+///     .with_replacement("ASAP", "as soon as possible");
+/// assert_eq!(
+///     "Mail john at example dot com as soon as possible, it costs 5 dollars.",
+///     normalizer.normalize("Mail john@example.com ASAP, it costs $5.")
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextNormalizer {
+    language: String,
+    replacements: Vec<(String, String)>,
+    locale_rules: bool,
+    verbalize_addresses: bool,
+    emoji: EmojiHandling,
+    say_as: bool,
+}
+
+impl Speak {
+    /// Normalize text using the default [`TextNormalizer`] for the language of the voice
+    pub fn from_normalized_text(text: &str, voice_settings: &VoiceSettings) -> Speak {
+        TextNormalizer::for_language(&voice_settings.language).convert(text, voice_settings)
+    }
+}
+
+/// Normalized text with say-as annotations
+enum Piece {
+    Text(String),
+    SayAs(String, InterpretAs, Option<&'static str>),
+}
+
+#[derive(Default)]
+struct Pieces(Vec<Piece>);
+
+impl Pieces {
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.0.last_mut() {
+            Some(Piece::Text(last)) => last.push_str(text),
+            _ => self.0.push(Piece::Text(text.to_owned())),
+        }
+    }
+
+    fn push_say_as(&mut self, text: &str, interpret_as: InterpretAs, format: Option<&'static str>) {
+        self.0
+            .push(Piece::SayAs(text.to_owned(), interpret_as, format));
+    }
+}
+
+impl TextNormalizer {
+    /// Normalizer with the rules of a locale such as `en-US`, only the language part is used
+    ///
+    /// Languages without rules only get user defined replacements, addresses are verbalized in English.
+    pub fn for_language(locale: &Locale) -> Self {
+        Self {
+            language: locale.language().to_owned(),
+            replacements: vec![],
+            locale_rules: true,
+            verbalize_addresses: true,
+            emoji: EmojiHandling::Strip,
+            say_as: false,
+        }
+    }
+
+    /// Replace a whole word, case sensitive. Takes precedence over the rules of the locale
+    pub fn with_replacement(mut self, word: &str, replacement: &str) -> Self {
+        self.replacements
+            .push((word.to_owned(), replacement.to_owned()));
+        self
+    }
+
+    /// Whether abbreviations, units and currencies of the locale are expanded
+    pub fn with_locale_rules(mut self, locale_rules: bool) -> Self {
+        self.locale_rules = locale_rules;
+        self
+    }
+
+    /// Whether urls and email addresses are read as words
+    pub fn with_verbalized_addresses(mut self, verbalize_addresses: bool) -> Self {
+        self.verbalize_addresses = verbalize_addresses;
+        self
+    }
+
+    pub fn with_emoji(mut self, emoji: EmojiHandling) -> Self {
+        self.emoji = emoji;
+        self
+    }
+
+    /// Whether numbers, ordinals, ISO dates and times are annotated with `say-as` in [`Self::to_segments`]
+    pub fn with_say_as(mut self, say_as: bool) -> Self {
+        self.say_as = say_as;
+        self
+    }
+
+    pub fn convert(&self, text: &str, voice_settings: &VoiceSettings) -> Speak {
+        Speak::segments_with_voice_settings(voice_settings, self.to_segments(text))
+    }
+
+    /// Normalized text, without say-as annotations
+    pub fn normalize(&self, text: &str) -> String {
+        self.pieces(text)
+            .0
+            .into_iter()
+            .map(|piece| match piece {
+                Piece::Text(text) | Piece::SayAs(text, _, _) => text,
+            })
+            .collect()
+    }
+
+    pub fn to_segments(&self, text: &str) -> Vec<VoiceSegment> {
+        self.pieces(text)
+            .0
+            .into_iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => VoiceSegment::Plain(text),
+                Piece::SayAs(text, interpret_as, format) => {
+                    VoiceSegment::say_as(&text, interpret_as, format)
+                }
+            })
+            .collect()
+    }
+
+    fn rules(&self) -> Option<&'static LocaleRules> {
+        LOCALE_RULES
+            .iter()
+            .find(|rules| rules.language == self.language)
+    }
+
+    fn pieces(&self, text: &str) -> Pieces {
+        let text = self.replace_emoji(text);
+        let mut pieces = Pieces::default();
+        let mut previous_number: Option<&str> = None;
+        let mut chunks = text.split_inclusive(char::is_whitespace);
+        while let Some(chunk) = chunks.next() {
+            let word = chunk.trim_end();
+            let ends_text = chunks.clone().all(|chunk| chunk.trim().is_empty());
+            previous_number = if word.is_empty() {
+                previous_number
+            } else {
+                self.push_word(word, previous_number, ends_text, &mut pieces)
+            };
+            pieces.push_text(&chunk[word.len()..]);
+        }
+        pieces
+    }
+
+    fn replace_emoji(&self, text: &str) -> String {
+        if self.emoji == EmojiHandling::Keep {
+            return text.to_owned();
+        }
+        let names = self.rules().map(|rules| rules.emoji_names).unwrap_or(&[]);
+        let mut replaced = String::with_capacity(text.len());
+        let mut characters = text.chars().peekable();
+        while let Some(character) = characters.next() {
+            if !is_emoji(character) {
+                replaced.push(character);
+                continue;
+            }
+            let name = names
+                .iter()
+                .find(|(emoji, _)| *emoji == character)
+                .map(|(_, name)| *name)
+                .filter(|_| self.emoji == EmojiHandling::Name);
+            if let Some(name) = name {
+                if !replaced.is_empty() && !replaced.ends_with(char::is_whitespace) {
+                    replaced.push(' ');
+                }
+                replaced.push_str(name);
+                if characters.peek().is_some_and(|next| next.is_alphanumeric()) {
+                    replaced.push(' ');
+                }
+            }
+        }
+        replaced
+    }
+
+    /// Normalize a word without whitespace, returns the number a following unit refers to
+    fn push_word<'a>(
+        &self,
+        word: &'a str,
+        previous_number: Option<&str>,
+        ends_text: bool,
+        pieces: &mut Pieces,
+    ) -> Option<&'a str> {
+        let core = word.trim_start_matches(is_leading_punctuation);
+        let lead = &word[..word.len() - core.len()];
+        let core = core.trim_end_matches(is_trailing_punctuation);
+        let mut trail = &word[lead.len() + core.len()..];
+        if core.is_empty() {
+            pieces.push_text(word);
+            return None;
+        }
+        pieces.push_text(lead);
+        // abbreviations are written with their period, which also ends the last sentence
+        let with_period = trail
+            .strip_prefix('.')
+            .map(|rest| (&word[lead.len()..word.len() - rest.len()], rest));
+        let replacement = match with_period {
+            Some((abbreviation, rest)) => match self.replacement(abbreviation) {
+                Some(replacement) => {
+                    if !ends_text {
+                        trail = rest;
+                    }
+                    Some(replacement)
+                }
+                None => self.replacement(core),
+            },
+            None => self.replacement(core),
+        };
+        let mut number = None;
+        if let Some(replacement) = replacement {
+            pieces.push_text(replacement);
+        } else if let Some(verbalized) = self.verbalize_address(core) {
+            pieces.push_text(&verbalized);
+        } else if let Some(unit) = previous_number.and_then(|number| self.unit(core, number)) {
+            pieces.push_text(unit);
+        } else if let Some((symbol, amount)) = core
+            .strip_prefix(CURRENCY_SYMBOLS)
+            .filter(|amount| is_number(amount))
+            .map(|amount| (&core[..core.len() - amount.len()], amount))
+            .filter(|(symbol, amount)| self.unit(symbol, amount).is_some())
+        {
+            self.push_number(amount, pieces);
+            pieces.push_text(" ");
+            pieces.push_text(self.unit(symbol, amount).unwrap_or_default());
+        } else if is_number(core) {
+            self.push_number(core, pieces);
+            number = Some(core).filter(|_| trail.is_empty());
+        } else if !self.push_annotated(core, pieces) {
+            let amount = number_prefix(core);
+            match self.unit(&core[amount.len()..], amount) {
+                Some(unit) if !amount.is_empty() => {
+                    self.push_number(amount, pieces);
+                    pieces.push_text(" ");
+                    pieces.push_text(unit);
+                }
+                _ => pieces.push_text(core),
+            }
+        }
+        pieces.push_text(trail);
+        number
+    }
+
+    fn replacement(&self, word: &str) -> Option<&str> {
+        let user = self
+            .replacements
+            .iter()
+            .find(|(pattern, _)| pattern == word)
+            .map(|(_, replacement)| replacement.as_str());
+        user.or_else(|| {
+            let lowercase = word.to_lowercase();
+            self.rules()
+                .filter(|_| self.locale_rules)?
+                .abbreviations
+                .iter()
+                .find(|(abbreviation, _)| abbreviation.to_lowercase() == lowercase)
+                .map(|(_, replacement)| *replacement)
+        })
+    }
+
+    /// Name of a unit or currency following `number`
+    fn unit(&self, symbol: &str, number: &str) -> Option<&'static str> {
+        let (_, singular, plural) = self
+            .rules()
+            .filter(|_| self.locale_rules)?
+            .units
+            .iter()
+            .find(|(unit, _, _)| *unit == symbol)?;
+        Some(if number.trim_start_matches('-') == "1" {
+            singular
+        } else {
+            plural
+        })
+    }
+
+    fn verbalize_address(&self, word: &str) -> Option<String> {
+        if !self.verbalize_addresses {
+            return None;
+        }
+        let address = if let Some((user, domain)) = word.split_once('@') {
+            if user.is_empty() || !domain.contains('.') || domain.starts_with('.') {
+                return None;
+            }
+            word
+        } else {
+            let url = word
+                .strip_prefix("https://")
+                .or_else(|| word.strip_prefix("http://"))
+                .or_else(|| word.strip_prefix("www.").map(|_| word))?;
+            url.trim_end_matches('/')
+        };
+        let rules = self.rules().unwrap_or(&ENGLISH);
+        let mut words = vec![];
+        let mut rest = address;
+        while let Some(separator) = rest.find(['@', '.', '/']) {
+            words.push(&rest[..separator]);
+            words.push(match rest.as_bytes()[separator] {
+                b'@' => rules.at,
+                b'.' => rules.dot,
+                _ => rules.slash,
+            });
+            rest = &rest[separator + 1..];
+        }
+        words.push(rest);
+        Some(words.join(" "))
+    }
+
+    fn push_number(&self, number: &str, pieces: &mut Pieces) {
+        if self.say_as {
+            pieces.push_say_as(number, InterpretAs::Cardinal, None);
+        } else {
+            pieces.push_text(number);
+        }
+    }
+
+    /// Annotate ordinals, ISO dates and times, returns false for other words
+    fn push_annotated(&self, word: &str, pieces: &mut Pieces) -> bool {
+        if !self.say_as {
+            return false;
+        }
+        let annotation = if is_iso_date(word) {
+            (InterpretAs::Date, Some("ymd"))
+        } else if is_time(word) {
+            (InterpretAs::Time, Some("hms24"))
+        } else if self.language == "en" && is_english_ordinal(word) {
+            (InterpretAs::Ordinal, None)
+        } else {
+            return false;
+        };
+        pieces.push_say_as(word, annotation.0, annotation.1);
+        true
+    }
+}
+
+fn is_leading_punctuation(character: char) -> bool {
+    matches!(
+        character,
+        '(' | '[' | '"' | '\'' | '«' | '“' | '‘' | '¿' | '¡'
+    )
+}
+
+fn is_trailing_punctuation(character: char) -> bool {
+    matches!(
+        character,
+        '.' | ',' | ';' | ':' | '!' | '?' | ')' | ']' | '"' | '\'' | '»' | '”' | '’' | '…'
+    )
+}
+
+fn is_emoji(character: char) -> bool {
+    matches!(
+        character,
+        '\u{1F1E6}'..='\u{1F1FF}'
+            | '\u{1F300}'..='\u{1FAFF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{FE0F}'
+            | '\u{200D}'
+    )
+}
+
+/// Digits with thousands and decimal separators, optionally negative
+///
+/// A separator used more than once groups thousands, the decimal separator is used at most
+/// once and comes last, so `192.168.1.1` and `1.2.3` aren't numbers.
+fn is_number(word: &str) -> bool {
+    let digits = word.strip_prefix('-').unwrap_or(word);
+    let last_separator = match digits.rfind([',', '.']) {
+        Some(index) => index,
+        None => return is_digits(digits, 1..=usize::MAX),
+    };
+    let separator = if digits[last_separator..].starts_with(',') {
+        ','
+    } else {
+        '.'
+    };
+    if digits.matches(separator).count() > 1 {
+        return is_grouped(digits, separator);
+    }
+    let group_separator = if separator == ',' { '.' } else { ',' };
+    is_grouped(&digits[..last_separator], group_separator)
+        && is_digits(&digits[last_separator + 1..], 1..=usize::MAX)
+}
+
+/// Digits, in groups of three after the first when `separator` is used
+fn is_grouped(digits: &str, separator: char) -> bool {
+    if !digits.contains(separator) {
+        return is_digits(digits, 1..=usize::MAX);
+    }
+    let mut groups = digits.split(separator);
+    groups.next().is_some_and(|first| is_digits(first, 1..=3))
+        && groups.all(|group| is_digits(group, 3..=3))
+}
+
+/// Longest number at the start of a word, such as `5` of `5km`
+fn number_prefix(word: &str) -> &str {
+    let end = word
+        .char_indices()
+        .find(|(index, c)| {
+            !(c.is_ascii_digit() || *c == ',' || *c == '.' || (*index == 0 && *c == '-'))
+        })
+        .map(|(index, _)| index)
+        .unwrap_or(word.len());
+    let number = word[..end].trim_end_matches([',', '.']);
+    if is_number(number) {
+        number
+    } else {
+        ""
+    }
+}
+
+fn is_digits(part: &str, lengths: std::ops::RangeInclusive<usize>) -> bool {
+    lengths.contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit())
+}
+
+/// `YYYY-MM-DD`
+fn is_iso_date(word: &str) -> bool {
+    let parts: Vec<&str> = word.split('-').collect();
+    matches!(parts.as_slice(), [year, month, day]
+        if is_digits(year, 4..=4) && is_digits(month, 2..=2) && is_digits(day, 2..=2))
+}
+
+/// `H:MM` or `HH:MM:SS`
+fn is_time(word: &str) -> bool {
+    let parts: Vec<&str> = word.split(':').collect();
+    let hours_and_minutes = |hours: &str, minutes: &str| {
+        is_digits(hours, 1..=2)
+            && hours.parse::<u8>().is_ok_and(|hours| hours < 24)
+            && is_digits(minutes, 2..=2)
+            && minutes.parse::<u8>().is_ok_and(|minutes| minutes < 60)
+    };
+    match parts.as_slice() {
+        [hours, minutes] => hours_and_minutes(hours, minutes),
+        [hours, minutes, seconds] => {
+            hours_and_minutes(hours, minutes)
+                && is_digits(seconds, 2..=2)
+                && seconds.parse::<u8>().is_ok_and(|seconds| seconds < 60)
+        }
+        _ => false,
+    }
+}
+
+fn is_english_ordinal(word: &str) -> bool {
+    let lowercase = word.to_ascii_lowercase();
+    ["st", "nd", "rd", "th"].iter().any(|suffix| {
+        lowercase
+            .strip_suffix(suffix)
+            .is_some_and(|digits| is_digits(digits, 1..=9))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_locale_rules() {
+        let english = TextNormalizer::for_language(&"en-US".parse().unwrap());
+        assert_eq!(
+            "It is 5 kilometers, 1 kilometer or 20 percent of 3.50 euros for apples, pears et cetera and more.",
+            english.normalize(
+                "It is 5km, 1 km or 20% of 3.50€ for apples, pears etc. and more."
+            )
+        );
+        assert_eq!(
+            "Es kostet 5 Euro, zum Beispiel heute.",
+            TextNormalizer::for_language(&"de-DE".parse().unwrap())
+                .normalize("Es kostet €5, z.B. heute.")
+        );
+        assert_eq!(
+            "It is 5km.",
+            english.with_locale_rules(false).normalize("It is 5km.")
+        );
+    }
+
+    #[test]
+    fn keeps_period_of_abbreviation_ending_text() {
+        let english = TextNormalizer::for_language(&"en-US".parse().unwrap());
+        assert_eq!("apples et cetera.", english.normalize("apples etc."));
+        assert_eq!("apples et cetera. \n", english.normalize("apples etc. \n"));
+        assert_eq!(
+            "apples et cetera and pears",
+            english.normalize("apples etc. and pears")
+        );
+    }
+
+    #[test]
+    fn numbers_have_at_most_one_decimal_separator() {
+        for number in ["5", "-3.50", "1,000", "1,234,567.89", "1.234.567,89", "0,5"] {
+            assert!(is_number(number), "{number}");
+        }
+        for word in [
+            "192.168.1.1",
+            "1.2.3",
+            "1,2,3",
+            "1.234,56.7",
+            "12,34,567",
+            "1.",
+            ",5",
+        ] {
+            assert!(!is_number(word), "{word}");
+        }
+        let segments = TextNormalizer::for_language(&"en-US".parse().unwrap())
+            .with_say_as(true)
+            .to_segments("Ping 192.168.1.1 now");
+        assert_eq!(vec![VoiceSegment::plain("Ping 192.168.1.1 now")], segments);
+    }
+
+    #[test]
+    fn verbalizes_addresses_and_emoji() {
+        let normalizer =
+            TextNormalizer::for_language(&"en-US".parse().unwrap()).with_emoji(EmojiHandling::Name);
+        assert_eq!(
+            "See example dot com slash docs or write to help at example dot org. Great thumbs up!",
+            normalizer
+                .normalize("See https://example.com/docs/ or write to help@example.org. Great👍!")
+        );
+        assert_eq!(
+            "Great!",
+            TextNormalizer::for_language(&"en-US".parse().unwrap()).normalize("Great🎉!")
+        );
+    }
+
+    #[test]
+    fn annotates_with_say_as() {
+        let segments = TextNormalizer::for_language(&"en-US".parse().unwrap())
+            .with_say_as(true)
+            .to_segments("On 2024-05-01 at 10:30 the 3rd of 1,000 people paid $5.");
+        assert_eq!(
+            vec![
+                VoiceSegment::plain("On "),
+                VoiceSegment::say_as("2024-05-01", InterpretAs::Date, Some("ymd")),
+                VoiceSegment::plain(" at "),
+                VoiceSegment::say_as("10:30", InterpretAs::Time, Some("hms24")),
+                VoiceSegment::plain(" the "),
+                VoiceSegment::say_as("3rd", InterpretAs::Ordinal, None),
+                VoiceSegment::plain(" of "),
+                VoiceSegment::say_as("1,000", InterpretAs::Cardinal, None),
+                VoiceSegment::plain(" people paid "),
+                VoiceSegment::say_as("5", InterpretAs::Cardinal, None),
+                VoiceSegment::plain(" dollars."),
+            ],
+            segments
+        );
+    }
+}
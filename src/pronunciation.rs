@@ -0,0 +1,531 @@
+/// Client side pronunciation dictionary applied to the text of a document.
+///
+/// Unlike a [`crate::Lexicon`], which the service downloads, matches are wrapped in `sub` or `phoneme`
+/// elements before the document is sent.
+use crate::{ssml_serializer::VoiceSegment, Locale, PhoneticAlphabet, Result, Speak, TtsError};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pronunciation {
+    /// Read as other text, a `sub` element
+    Alias(String),
+    /// Read as a phonetic transcription, a `phoneme` element
+    Phoneme {
+        alphabet: PhoneticAlphabet,
+        ph: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PronunciationEntry {
+    pub term: String,
+    pub pronunciation: Pronunciation,
+    /// Only match the term between non alphanumeric characters, true by default
+    pub whole_word: bool,
+    /// True by default, so `US` doesn't match `us`
+    pub case_sensitive: bool,
+    /// Only apply to text of a locale such as `en-US`, or of a language such as `en`
+    pub locale: Option<Locale>,
+}
+
+impl PronunciationEntry {
+    pub fn alias(term: &str, alias: &str) -> Self {
+        Self::new(term, Pronunciation::Alias(alias.to_owned()))
+    }
+
+    pub fn phoneme(term: &str, alphabet: PhoneticAlphabet, ph: &str) -> Self {
+        Self::new(
+            term,
+            Pronunciation::Phoneme {
+                alphabet,
+                ph: ph.to_owned(),
+            },
+        )
+    }
+
+    fn new(term: &str, pronunciation: Pronunciation) -> Self {
+        Self {
+            term: term.to_owned(),
+            pronunciation,
+            whole_word: true,
+            case_sensitive: true,
+            locale: None,
+        }
+    }
+
+    pub fn with_whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    fn applies_to(&self, language: &Locale) -> bool {
+        self.locale
+            .as_ref()
+            .is_none_or(|locale| locale.includes(language))
+    }
+
+    /// End of the match starting at `start`
+    fn match_at(&self, text: &str, start: usize) -> Option<usize> {
+        if self.term.is_empty() {
+            return None;
+        }
+        let length = if self.case_sensitive {
+            Some(self.term.len()).filter(|_| text[start..].starts_with(&self.term))
+        } else {
+            caseless_prefix(&text[start..], &self.term)
+        }?;
+        let end = start + length;
+        let is_boundary = |character: Option<char>| !character.is_some_and(char::is_alphanumeric);
+        let whole_word = is_boundary(text[..start].chars().next_back())
+            && is_boundary(text[end..].chars().next());
+        Some(end).filter(|_| whole_word || !self.whole_word)
+    }
+
+    fn to_segment(&self, text: &str) -> VoiceSegment {
+        match &self.pronunciation {
+            Pronunciation::Alias(alias) => VoiceSegment::sub(text, alias),
+            Pronunciation::Phoneme { alphabet, ph } => VoiceSegment::phoneme(text, *alphabet, ph),
+        }
+    }
+}
+
+/// Entry as written in a dictionary file
+#[derive(Debug, Default, Deserialize)]
+struct RawEntry {
+    term: String,
+    alias: Option<String>,
+    phoneme: Option<String>,
+    alphabet: Option<String>,
+    locale: Option<String>,
+    case_sensitive: Option<bool>,
+    whole_word: Option<bool>,
+}
+
+impl RawEntry {
+    fn into_entry(self) -> std::result::Result<PronunciationEntry, String> {
+        if self.term.is_empty() {
+            return Err("empty term".to_owned());
+        }
+        let mut entry = match (self.alias, self.phoneme) {
+            (Some(alias), None) => PronunciationEntry::alias(&self.term, &alias),
+            (None, Some(ph)) => {
+                let alphabet = match self.alphabet {
+                    Some(alphabet) => alphabet
+                        .parse()
+                        .map_err(|_| format!("unknown phonetic alphabet {alphabet:?}"))?,
+                    None => PhoneticAlphabet::Ipa,
+                };
+                PronunciationEntry::phoneme(&self.term, alphabet, &ph)
+            }
+            _ => {
+                return Err(format!(
+                    "{:?} needs either an alias or a phoneme",
+                    self.term
+                ))
+            }
+        };
+        entry.locale = self
+            .locale
+            .map(|locale| locale.parse())
+            .transpose()
+            .map_err(|error: TtsError| error.to_string())?;
+        entry.case_sensitive = self.case_sensitive.unwrap_or(entry.case_sensitive);
+        entry.whole_word = self.whole_word.unwrap_or(entry.whole_word);
+        Ok(entry)
+    }
+}
+
+#[cfg(feature = "toml-dictionary")]
+#[derive(Deserialize)]
+struct RawDictionary {
+    #[serde(default, rename = "entry")]
+    entries: Vec<RawEntry>,
+}
+
+/// Terms with their pronunciation, applied using [`Speak::with_pronunciations`]
+///
+/// ```
+/// use azure_tts::{PronunciationDictionary, Speak, VoiceSettings};
+///
+/// let dictionary = PronunciationDictionary::from_csv_str(
+///     "term,alias,locale\nSQL,sequel,en\nW3C,World Wide Web Consortium,",
+/// )
+/// .unwrap();
+/// let speak = Speak::text_with_voice_settings(&VoiceSettings::default_female_jenny(), "SQL at W3C")
+///     .with_pronunciations(&dictionary);
+/// assert!(speak.to_ssml_xml().contains(r#"<sub alias="sequel">SQL</sub>"#));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PronunciationDictionary {
+    pub entries: Vec<PronunciationEntry>,
+}
+
+impl Speak {
+    /// Apply a [`PronunciationDictionary`] to the text of every voice, using the language of the voice
+    pub fn with_pronunciations(mut self, dictionary: &PronunciationDictionary) -> Self {
+        for voice in &mut self.voices {
+            let language = voice.xml_lang.as_ref().unwrap_or(&self.xml_lang);
+            voice.body = dictionary.apply(std::mem::take(&mut voice.body), language);
+        }
+        self
+    }
+}
+
+impl PronunciationDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_alias(self, term: &str, alias: &str) -> Self {
+        self.with_entry(PronunciationEntry::alias(term, alias))
+    }
+
+    /// Pronunciation in [`PhoneticAlphabet::Ipa`]
+    pub fn with_phoneme(self, term: &str, ph: &str) -> Self {
+        self.with_entry(PronunciationEntry::phoneme(term, PhoneticAlphabet::Ipa, ph))
+    }
+
+    pub fn with_entry(mut self, entry: PronunciationEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Parse comma separated values with a header row
+    ///
+    /// Columns are `term` and either `alias` or `phoneme`, optionally `alphabet` (ipa by default),
+    /// `locale`, `case_sensitive` and `whole_word` (true or false). Empty cells are ignored.
+    /// Unknown columns, rows with more cells than the header and unterminated quotes are errors.
+    pub fn from_csv_str(csv: &str) -> Result<Self> {
+        let mut rows = csv
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                parse_csv_line(line)
+                    .map(|row| (index + 1, row))
+                    .map_err(|message| {
+                        TtsError::InvalidValue(format!("line {}: {message}", index + 1))
+                    })
+            });
+        let header = match rows.next() {
+            Some(header) => header?.1,
+            None => return Ok(Self::new()),
+        };
+        let mut dictionary = Self::new();
+        for row in rows {
+            let (line, row) = row?;
+            let error = |message: String| TtsError::InvalidValue(format!("line {line}: {message}"));
+            if row.len() > header.len() {
+                return Err(error(format!(
+                    "{} cells but only {} columns",
+                    row.len(),
+                    header.len()
+                )));
+            }
+            let mut raw = RawEntry::default();
+            for (column, value) in header.iter().zip(row) {
+                let value = value.trim();
+                if value.is_empty() {
+                    continue;
+                }
+                let flag = || match value.to_ascii_lowercase().as_str() {
+                    "true" => Ok(true),
+                    "false" => Ok(false),
+                    _ => Err(error(format!("expected true or false for {column}"))),
+                };
+                match column.trim() {
+                    "term" => raw.term = value.to_owned(),
+                    "alias" => raw.alias = Some(value.to_owned()),
+                    "phoneme" => raw.phoneme = Some(value.to_owned()),
+                    "alphabet" => raw.alphabet = Some(value.to_owned()),
+                    "locale" => raw.locale = Some(value.to_owned()),
+                    "case_sensitive" => raw.case_sensitive = Some(flag()?),
+                    "whole_word" => raw.whole_word = Some(flag()?),
+                    other => return Err(error(format!("unknown column {other:?}"))),
+                }
+            }
+            dictionary.entries.push(raw.into_entry().map_err(error)?);
+        }
+        Ok(dictionary)
+    }
+
+    /// Parse `[[entry]]` tables with the keys of [`Self::from_csv_str`]
+    #[cfg(feature = "toml-dictionary")]
+    pub fn from_toml_str(toml: &str) -> Result<Self> {
+        let raw: RawDictionary =
+            toml::from_str(toml).map_err(|error| TtsError::InvalidValue(error.to_string()))?;
+        let entries = raw
+            .entries
+            .into_iter()
+            .map(RawEntry::into_entry)
+            .collect::<std::result::Result<_, _>>()
+            .map_err(TtsError::InvalidValue)?;
+        Ok(Self { entries })
+    }
+
+    /// Wrap terms in plain text of `language`, text of `lang` elements uses their language
+    pub fn apply(&self, segments: Vec<VoiceSegment>, language: &Locale) -> Vec<VoiceSegment> {
+        let mut applied = vec![];
+        for segment in segments {
+            let segment = match segment {
+                VoiceSegment::Plain(text) => {
+                    applied.extend(self.apply_text(&text, language));
+                    continue;
+                }
+                VoiceSegment::ExpressAs(mut express_as) => {
+                    express_as.body = self.apply(express_as.body, language);
+                    VoiceSegment::ExpressAs(express_as)
+                }
+                VoiceSegment::Emphasis(mut emphasis) => {
+                    emphasis.body = self.apply(emphasis.body, language);
+                    VoiceSegment::Emphasis(emphasis)
+                }
+                VoiceSegment::Lang(mut lang) => {
+                    lang.body = self.apply(lang.body, &lang.xml_lang);
+                    VoiceSegment::Lang(lang)
+                }
+                VoiceSegment::Paragraph(mut paragraph) => {
+                    paragraph.body = self.apply(paragraph.body, language);
+                    VoiceSegment::Paragraph(paragraph)
+                }
+                VoiceSegment::Sentence(mut sentence) => {
+                    sentence.body = self.apply(sentence.body, language);
+                    VoiceSegment::Sentence(sentence)
+                }
+                VoiceSegment::Prosody(mut prosody) => {
+                    prosody.body = self.apply(prosody.body, language);
+                    VoiceSegment::Prosody(prosody)
+                }
+                other => other,
+            };
+            applied.push(segment);
+        }
+        applied
+    }
+
+    fn apply_text(&self, text: &str, language: &Locale) -> Vec<VoiceSegment> {
+        let entries: Vec<&PronunciationEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.applies_to(language))
+            .collect();
+        let mut segments = vec![];
+        let mut rest = text;
+        // the longest term wins at the earliest position
+        while let Some((start, end, entry)) = rest.char_indices().find_map(|(start, _)| {
+            entries
+                .iter()
+                .filter_map(|entry| entry.match_at(rest, start).map(|end| (start, end, *entry)))
+                .max_by_key(|(_, end, _)| *end)
+        }) {
+            if start > 0 {
+                segments.push(VoiceSegment::plain(&rest[..start]));
+            }
+            segments.push(entry.to_segment(&rest[start..end]));
+            rest = &rest[end..];
+        }
+        if !rest.is_empty() {
+            segments.push(VoiceSegment::plain(rest));
+        }
+        segments
+    }
+}
+
+/// Length of the start of `text` that is `term` ignoring case
+///
+/// Compared character by character, the matched text may be longer or shorter than the term
+/// as for `STRASSE` and `straße`.
+fn caseless_prefix(text: &str, term: &str) -> Option<usize> {
+    let mut folded_term = vec![];
+    term.chars()
+        .for_each(|character| fold_case(character, &mut folded_term));
+    let mut folded = vec![];
+    for (index, character) in text.char_indices() {
+        fold_case(character, &mut folded);
+        if !folded_term.starts_with(&folded) {
+            return None;
+        }
+        if folded.len() == folded_term.len() {
+            return Some(index + character.len_utf8());
+        }
+    }
+    None
+}
+
+/// Append the characters `character` is compared as when case is ignored
+fn fold_case(character: char, folded: &mut Vec<char>) {
+    match character {
+        'ß' | 'ẞ' => folded.extend(['s', 's']),
+        // the lowercase has a combining dot which the term usually doesn't
+        'İ' => folded.push('i'),
+        _ => folded.extend(character.to_lowercase()),
+    }
+}
+
+/// Fields of a line, quoted fields may contain commas and doubled quotes
+fn parse_csv_line(line: &str) -> std::result::Result<Vec<String>, String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut characters = line.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if quoted && characters.peek() == Some(&'"') => {
+                characters.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(character),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote".to_owned());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VoiceSettings;
+
+    #[test]
+    fn applies_whole_words_and_case() {
+        let dictionary = PronunciationDictionary::new()
+            .with_alias("US", "United States")
+            .with_entry(PronunciationEntry::alias("kb", "kilobyte").with_case_sensitive(false))
+            .with_entry(
+                PronunciationEntry::phoneme("tomato", PhoneticAlphabet::Ipa, "təˈmɑːtoʊ")
+                    .with_whole_word(false),
+            );
+        assert_eq!(
+            vec![
+                VoiceSegment::plain("Tell us about the "),
+                VoiceSegment::sub("US", "United States"),
+                VoiceSegment::plain(", "),
+                VoiceSegment::sub("KB", "kilobyte"),
+                VoiceSegment::plain(" and "),
+                VoiceSegment::phoneme("tomato", PhoneticAlphabet::Ipa, "təˈmɑːtoʊ"),
+                VoiceSegment::plain("es, not USB."),
+            ],
+            dictionary.apply(
+                vec![VoiceSegment::plain(
+                    "Tell us about the US, KB and tomatoes, not USB."
+                )],
+                &Locale::en_us()
+            )
+        );
+    }
+
+    #[test]
+    fn respects_locale_scope() {
+        let dictionary = PronunciationDictionary::from_csv_str(
+            "term,alias,locale\nGIF,jif,en-US\n\"Brot, frisch\",\"frisches \"\"Brot\"\"\",de\n",
+        )
+        .unwrap();
+        let voice = VoiceSettings::default_female_jenny();
+        let german: Locale = "de-DE".parse().unwrap();
+        let speak = Speak::segments_with_voice_settings(
+            &voice,
+            vec![
+                VoiceSegment::plain("A GIF"),
+                VoiceSegment::lang(&german, vec![VoiceSegment::plain("GIF und Brot, frisch")]),
+            ],
+        )
+        .with_pronunciations(&dictionary);
+        assert_eq!(
+            &[
+                VoiceSegment::plain("A "),
+                VoiceSegment::sub("GIF", "jif"),
+                VoiceSegment::lang(
+                    &german,
+                    vec![
+                        VoiceSegment::plain("GIF und "),
+                        VoiceSegment::sub("Brot, frisch", "frisches \"Brot\""),
+                    ]
+                ),
+            ],
+            speak.voices()[0].segments()
+        );
+    }
+
+    #[test]
+    fn reports_invalid_rows() {
+        let error =
+            PronunciationDictionary::from_csv_str("term,alias,phoneme\nSQL,,\n").unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+        assert!(PronunciationDictionary::from_csv_str("term,aliases\nSQL,sequel").is_err());
+        assert!(
+            PronunciationDictionary::from_csv_str("term,phoneme,alphabet\na,b,klingon").is_err()
+        );
+        let error =
+            PronunciationDictionary::from_csv_str("term,alias\nSQL,sequel,extra").unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+        let error =
+            PronunciationDictionary::from_csv_str("term,alias\nSQL,\"sequel\n").unwrap_err();
+        assert!(error.to_string().contains("unterminated quote"));
+        assert!(PronunciationDictionary::from_csv_str("\"term,alias\nSQL,sequel").is_err());
+    }
+
+    #[test]
+    fn ignores_case_by_character() {
+        let dictionary = PronunciationDictionary::new()
+            .with_entry(PronunciationEntry::alias("straße", "street").with_case_sensitive(false))
+            .with_entry(PronunciationEntry::alias("istanbul", "city").with_case_sensitive(false));
+        assert_eq!(
+            vec![
+                VoiceSegment::sub("STRASSE", "street"),
+                VoiceSegment::plain(" in "),
+                VoiceSegment::sub("İstanbul", "city"),
+            ],
+            dictionary.apply(
+                vec![VoiceSegment::plain("STRASSE in İstanbul")],
+                &"de-DE".parse().unwrap()
+            )
+        );
+        assert_eq!(Some(2), caseless_prefix("ßa", "SS"));
+        assert_eq!(None, caseless_prefix("ßa", "S"));
+    }
+
+    #[cfg(feature = "toml-dictionary")]
+    #[test]
+    fn parses_toml() {
+        let dictionary = PronunciationDictionary::from_toml_str(
+            r#"
+            [[entry]]
+            term = "Azure"
+            phoneme = "ˈæʒər"
+            locale = "en"
+
+            [[entry]]
+            term = "btw"
+            alias = "by the way"
+            case_sensitive = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            PronunciationDictionary::new()
+                .with_entry(
+                    PronunciationEntry::phoneme("Azure", PhoneticAlphabet::Ipa, "ˈæʒər")
+                        .with_locale("en".parse().unwrap())
+                )
+                .with_entry(
+                    PronunciationEntry::alias("btw", "by the way").with_case_sensitive(false)
+                ),
+            dictionary
+        );
+    }
+}
@@ -0,0 +1,231 @@
+/// Sentence segmentation used to split text into requests and phrases.
+use crate::Locale;
+
+/// Abbreviations ending with a period that rarely end a sentence, without the final period
+const COMMON_ABBREVIATIONS: &[&str] = &["e.g", "i.e", "vs", "dr", "prof", "fig", "approx"];
+
+const LANGUAGE_ABBREVIATIONS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "mr", "mrs", "ms", "jr", "sr", "st", "mt", "inc", "ltd", "co", "corp", "dept", "jan",
+            "feb", "mar", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov", "dec", "a.m",
+            "p.m", "u.s", "u.k",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "z.b", "d.h", "u.a", "bzw", "ca", "nr", "str", "vgl", "evtl", "ggf", "hr", "fr", "s",
+        ],
+    ),
+    ("fr", &["m", "mme", "mlle", "p.ex", "cf", "av", "bd", "env"]),
+    (
+        "es",
+        &["sr", "sra", "srta", "dra", "ud", "uds", "p.ej", "av", "pág"],
+    ),
+    ("it", &["sig", "sig.ra", "dott", "ing", "avv", "ecc", "pag"]),
+    ("pt", &["sr", "sra", "dra", "av", "pág", "p.ex"]),
+    ("nl", &["dhr", "mevr", "bijv", "o.a", "d.w.z", "blz"]),
+];
+
+/// Closing quotes and brackets that belong to the sentence they follow
+fn is_closing(character: char) -> bool {
+    matches!(
+        character,
+        '"' | '\'' | ')' | ']' | '»' | '”' | '’' | '」' | '』' | '）' | '】' | '》'
+    )
+}
+
+fn is_terminal(character: char) -> bool {
+    matches!(character, '.' | '!' | '?' | '…')
+}
+
+/// Full width terminal punctuation which isn't followed by whitespace
+fn is_cjk_terminal(character: char) -> bool {
+    matches!(character, '。' | '！' | '？' | '．' | '｡')
+}
+
+/// Sentence segmentation aware of the conventions of a language.
+///
+/// Periods after known abbreviations, initials and inside of numbers don't end a sentence,
+/// CJK full stops end a sentence without following whitespace and Thai sentences are separated
+/// by spaces.
+///
+/// Thai text is split at every run of whitespace. Spaces also separate clauses and items in
+/// Thai, so these are returned as sentences of their own; telling them apart needs a
+/// dictionary based segmenter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentenceSegmenter {
+    abbreviations: Vec<String>,
+    /// Whitespace separates sentences, as in Thai, including the clauses it also separates
+    whitespace_boundaries: bool,
+}
+
+impl SentenceSegmenter {
+    /// Segmenter for a locale such as `en-US`, only the language part is used
+    pub fn for_language(locale: &Locale) -> Self {
+        Self::with_language(locale.language())
+    }
+
+    fn with_language(language: &str) -> Self {
+        let mut abbreviations: Vec<String> = COMMON_ABBREVIATIONS
+            .iter()
+            .map(|abbreviation| abbreviation.to_string())
+            .collect();
+        if let Some((_, language_abbreviations)) = LANGUAGE_ABBREVIATIONS
+            .iter()
+            .find(|(name, _)| *name == language)
+        {
+            abbreviations.extend(
+                language_abbreviations
+                    .iter()
+                    .map(|abbreviation| abbreviation.to_string()),
+            );
+        }
+        Self {
+            abbreviations,
+            whitespace_boundaries: language == "th",
+        }
+    }
+
+    /// Additional abbreviation, with or without the final period
+    pub fn with_abbreviation(mut self, abbreviation: &str) -> Self {
+        self.abbreviations
+            .push(abbreviation.trim_end_matches('.').to_lowercase());
+        self
+    }
+
+    /// Split text into trimmed sentences
+    pub fn sentences<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut sentences = vec![];
+        let mut start = 0;
+        let mut characters = text.char_indices().peekable();
+        while let Some((index, character)) = characters.next() {
+            let boundary = if is_cjk_terminal(character) {
+                while let Some((_, next)) = characters.peek() {
+                    if is_cjk_terminal(*next) || is_closing(*next) {
+                        characters.next();
+                    } else {
+                        break;
+                    }
+                }
+                true
+            } else if is_terminal(character) {
+                let mut only_periods = character == '.';
+                while let Some((_, next)) = characters.peek() {
+                    if is_terminal(*next) || is_closing(*next) {
+                        only_periods &= *next != '!' && *next != '?';
+                        characters.next();
+                    } else {
+                        break;
+                    }
+                }
+                let end = characters.peek().map(|(end, _)| *end).unwrap_or(text.len());
+                let followed_by_space = text[end..].chars().next().is_none_or(char::is_whitespace);
+                followed_by_space
+                    && !(only_periods && self.continues(&text[start..index], &text[end..]))
+            } else {
+                self.whitespace_boundaries && character.is_whitespace()
+            };
+            if boundary {
+                let end = characters.peek().map(|(end, _)| *end).unwrap_or(text.len());
+                let sentence = text[start..end].trim();
+                if !sentence.is_empty() {
+                    sentences.push(sentence);
+                }
+                start = end;
+            }
+        }
+        let rest = text[start..].trim();
+        if !rest.is_empty() {
+            sentences.push(rest);
+        }
+        sentences
+    }
+
+    /// Whether a period after `before` is part of an abbreviation, initial or ellipsis in a sentence
+    fn continues(&self, before: &str, after: &str) -> bool {
+        let word = before
+            .rsplit(char::is_whitespace)
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        let is_initial = word.chars().count() == 1 && word.chars().all(char::is_alphabetic);
+        let next_is_lowercase = after
+            .trim_start()
+            .chars()
+            .next()
+            .is_some_and(char::is_lowercase);
+        is_initial || next_is_lowercase || self.abbreviations.contains(&word)
+    }
+}
+
+impl Default for SentenceSegmenter {
+    /// Segmenter without language specific abbreviations
+    fn default() -> Self {
+        Self::with_language("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_abbreviations_and_numbers() {
+        let segmenter = SentenceSegmenter::for_language(&"en-US".parse().unwrap());
+        assert_eq!(
+            vec![
+                "Dr. Smith paid $3.50 for it, e.g. on Jan. 5th.",
+                "J. R. R. Tolkien wrote it!",
+                "Really?!",
+                "\"Yes.\"",
+                "Done",
+            ],
+            segmenter.sentences(
+                "Dr. Smith paid $3.50 for it, e.g. on Jan. 5th. J. R. R. Tolkien wrote it! \
+                 Really?! \"Yes.\" Done"
+            )
+        );
+    }
+
+    #[test]
+    fn language_specific_abbreviations() {
+        let text = "Das ist z.B. gut. Nr. 5 folgt.";
+        assert_eq!(
+            vec!["Das ist z.B. gut.", "Nr. 5 folgt."],
+            SentenceSegmenter::for_language(&"de-DE".parse().unwrap()).sentences(text)
+        );
+        assert_eq!(
+            vec!["Das ist z.B. gut.", "Nr.", "5 folgt."],
+            SentenceSegmenter::for_language(&"en-US".parse().unwrap()).sentences(text)
+        );
+        assert_eq!(
+            vec!["See Sect. 5 please."],
+            SentenceSegmenter::for_language(&"en-US".parse().unwrap())
+                .with_abbreviation("Sect.")
+                .sentences("See Sect. 5 please.")
+        );
+    }
+
+    #[test]
+    fn cjk_and_thai() {
+        assert_eq!(
+            vec!["今天天气很好。", "我们去公园吧！", "好吗？"],
+            SentenceSegmenter::for_language(&"zh-CN".parse().unwrap())
+                .sentences("今天天气很好。我们去公园吧！好吗？")
+        );
+        assert_eq!(
+            vec!["「こんにちは。」", "元気です。"],
+            SentenceSegmenter::for_language(&"ja-JP".parse().unwrap())
+                .sentences("「こんにちは。」元気です。")
+        );
+        assert_eq!(
+            vec!["สวัสดีครับ", "วันนี้อากาศดี"],
+            SentenceSegmenter::for_language(&"th-TH".parse().unwrap())
+                .sentences("สวัสดีครับ วันนี้อากาศดี")
+        );
+    }
+}
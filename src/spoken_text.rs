@@ -0,0 +1,239 @@
+/// Extraction of the text that is actually spoken from ssml.
+///
+/// Used for logging, captions and billing. `sub` elements are read as their alias,
+/// audio and bookmarks are skipped and whitespace is collapsed the way it's spoken.
+/// Offsets are byte offsets, so spans slice the spoken text and the ssml directly.
+use crate::{xml::parse_error, Result, Speak};
+use quick_xml::{events::Event, Reader};
+use std::ops::Range;
+
+/// Spoken text with spans mapping it back to the ssml it was read from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpokenText {
+    pub text: String,
+    pub spans: Vec<TextSpan>,
+}
+
+/// Byte range of spoken text and the byte range of the ssml that produced it
+///
+/// The ranges are byte offsets rather than character offsets; count
+/// `text[..range.start].chars()` where character positions are needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextSpan {
+    /// Byte range in [`SpokenText::text`]
+    pub text_range: Range<usize>,
+    /// Byte range in the ssml
+    pub ssml_range: Range<usize>,
+}
+
+impl SpokenText {
+    /// Span covering the spoken text at byte `offset` of [`SpokenText::text`]
+    pub fn span_at(&self, offset: usize) -> Option<&TextSpan> {
+        self.spans
+            .iter()
+            .find(|span| span.text_range.contains(&offset))
+    }
+
+    fn push(&mut self, text: &str, ssml_range: Range<usize>) {
+        let mut collapsed = String::with_capacity(text.len());
+        for character in text.chars() {
+            if !character.is_whitespace() {
+                collapsed.push(character);
+            } else if !collapsed.ends_with(' ') {
+                collapsed.push(' ');
+            }
+        }
+        let collapsed = if self.text.is_empty() || self.text.ends_with(' ') {
+            collapsed.trim_start()
+        } else {
+            &collapsed
+        };
+        if collapsed.is_empty() {
+            return;
+        }
+        let start = self.text.len();
+        self.text.push_str(collapsed);
+        self.spans.push(TextSpan {
+            text_range: start..self.text.len(),
+            ssml_range,
+        });
+    }
+
+    /// Separate text of consecutive voices, paragraphs and sentences
+    fn separate(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with(' ') {
+            self.text.push(' ');
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        if self.text.ends_with(' ') {
+            self.text.pop();
+            let length = self.text.len();
+            if let Some(span) = self.spans.last_mut() {
+                span.text_range.end = span.text_range.end.min(length);
+            }
+        }
+        self
+    }
+}
+
+impl Speak {
+    /// Spoken text with spans pointing into [`Speak::to_ssml_xml`]
+    pub fn spoken_text(&self) -> SpokenText {
+        spoken_text(&self.to_ssml_xml()).expect("serialized ssml is well formed")
+    }
+}
+
+/// Extract the spoken text of an ssml document
+pub fn spoken_text(ssml: &str) -> Result<SpokenText> {
+    let mut reader = Reader::from_str(ssml);
+    let mut buf = Vec::new();
+    let mut skip_buf = Vec::new();
+    let mut spoken = SpokenText::default();
+    let mut voice_depth = 0;
+    loop {
+        let position = reader.buffer_position();
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref element)) => match element.local_name() {
+                b"voice" => {
+                    spoken.separate();
+                    voice_depth += 1;
+                }
+                b"p" | b"s" => spoken.separate(),
+                b"sub" => {
+                    let alias = alias(&reader, element, ssml, position)?;
+                    let name = element.name().to_vec();
+                    reader
+                        .read_to_end(name, &mut skip_buf)
+                        .map_err(|e| parse_error(ssml, reader.buffer_position(), e))?;
+                    if voice_depth > 0 {
+                        spoken.push(&alias, position..reader.buffer_position());
+                    }
+                }
+                // audio fallback text is only spoken when the audio can't be played
+                b"audio" | b"lexicon" | b"bookmark" => {
+                    let name = element.name().to_vec();
+                    reader
+                        .read_to_end(name, &mut skip_buf)
+                        .map_err(|e| parse_error(ssml, reader.buffer_position(), e))?;
+                    // the audio is played between the surrounding words
+                    if element.local_name() == b"audio" {
+                        spoken.separate();
+                    }
+                }
+                _ => (),
+            },
+            Ok(Event::Empty(ref element)) => match element.local_name() {
+                b"sub" => {
+                    let alias = alias(&reader, element, ssml, position)?;
+                    if voice_depth > 0 {
+                        spoken.push(&alias, position..reader.buffer_position());
+                    }
+                }
+                b"audio" => spoken.separate(),
+                _ => (),
+            },
+            Ok(Event::End(ref element)) => match element.local_name() {
+                b"voice" => {
+                    voice_depth -= 1;
+                    spoken.separate();
+                }
+                b"p" | b"s" => spoken.separate(),
+                _ => (),
+            },
+            Ok(Event::Text(ref text)) | Ok(Event::CData(ref text)) if voice_depth > 0 => {
+                let text = text
+                    .unescape_and_decode(&reader)
+                    .map_err(|e| parse_error(ssml, position, e))?;
+                spoken.push(&text, position..reader.buffer_position());
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => (),
+            Err(error) => return Err(parse_error(ssml, reader.buffer_position(), error)),
+        }
+        buf.clear();
+    }
+    Ok(spoken.finish())
+}
+
+fn alias(
+    reader: &Reader<&[u8]>,
+    element: &quick_xml::events::BytesStart,
+    ssml: &str,
+    position: usize,
+) -> Result<String> {
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| parse_error(ssml, position, e))?;
+        if attribute.key == b"alias" {
+            return attribute
+                .unescape_and_decode_value(reader)
+                .map_err(|e| parse_error(ssml, position, e));
+        }
+    }
+    Err(parse_error(ssml, position, "sub without alias"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnUsVoices, VoiceSegment};
+
+    #[test]
+    fn spoken_text_of_speak() {
+        let speak = Speak::segments_with_voice_settings(
+            &EnUsVoices::JennyNeural.to_voice_settings(),
+            vec![
+                VoiceSegment::paragraph(vec![VoiceSegment::plain("Fish & chips")]),
+                VoiceSegment::bookmark("middle"),
+                VoiceSegment::sub("BTW", "by the way"),
+                VoiceSegment::audio(
+                    "https://example.com/beep.wav",
+                    vec![VoiceSegment::plain("beep")],
+                )
+                .unwrap(),
+                VoiceSegment::plain("done"),
+            ],
+        );
+        let ssml = speak.to_ssml_xml();
+        let spoken = speak.spoken_text();
+
+        assert_eq!("Fish & chips by the way done", spoken.text);
+        let texts: Vec<_> = spoken
+            .spans
+            .iter()
+            .map(|span| {
+                (
+                    &spoken.text[span.text_range.clone()],
+                    &ssml[span.ssml_range.clone()],
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("Fish & chips", "Fish &amp; chips"),
+                ("by the way", "<sub alias=\"by the way\">BTW</sub>"),
+                ("done", "done"),
+            ],
+            texts
+        );
+        assert_eq!(
+            "<sub alias=\"by the way\">BTW</sub>",
+            &ssml[spoken.span_at(15).unwrap().ssml_range.clone()]
+        );
+    }
+
+    #[test]
+    fn spoken_text_of_raw_ssml() {
+        let ssml = r#"<speak version="1.0" xml:lang="en-US">
+    <voice name="en-US-JennyNeural">
+        <s>Hello</s><s>world<audio src="https://example.com/beep.wav"/>and
+        <sub alias="everyone">all</sub></s>
+    </voice>
+    <voice name="en-US-GuyNeural"><p>Bye</p></voice>
+</speak>"#;
+        let spoken = spoken_text(ssml).unwrap();
+        assert_eq!("Hello world and everyone Bye", spoken.text);
+        assert_eq!(5, spoken.spans.len());
+    }
+}
@@ -0,0 +1,347 @@
+/// Fluent builder for [`Speak`] documents.
+///
+/// ```
+/// use azure_tts::{EnUsVoices, Speak, Style};
+/// use std::time::Duration;
+///
+/// let speak = Speak::builder(&EnUsVoices::JennyNeural.to_voice_settings())
+///     .text("Hello")
+///     .pause(Duration::from_millis(500))
+///     .style(Style::Cheerful, |b| b.text("world"))
+///     .build()
+///     .unwrap();
+/// ```
+use crate::{
+    ssml_serializer::{BackgroundAudio, Expression, ProsodySettings, Speak, Voice, VoiceSegment},
+    BreakStrength, EmphasisLevel, InterpretAs, Locale, PhoneticAlphabet, PronunciationDictionary,
+    Result, SilenceAttributeType, TtsError, VoiceSettings,
+};
+use std::time::Duration;
+
+/// Methods adding segments, shared by [`SpeakBuilder`] and [`SegmentBuilder`]
+macro_rules! segment_methods {
+    () => {
+        pub fn text(self, text: &str) -> Self {
+            self.push(Ok(VoiceSegment::plain(text)))
+        }
+
+        pub fn segment(self, segment: VoiceSegment) -> Self {
+            self.push(Ok(segment))
+        }
+
+        pub fn pause(self, time: Duration) -> Self {
+            self.push(VoiceSegment::pause(time))
+        }
+
+        pub fn pause_with_strength(self, strength: BreakStrength) -> Self {
+            self.push(Ok(VoiceSegment::pause_with_strength(strength)))
+        }
+
+        pub fn silence(self, attribute_type: SilenceAttributeType, value: Duration) -> Self {
+            self.push(VoiceSegment::silence(attribute_type, value))
+        }
+
+        pub fn sub(self, text: &str, alias: &str) -> Self {
+            self.push(Ok(VoiceSegment::sub(text, alias)))
+        }
+
+        pub fn say_as(self, text: &str, interpret_as: InterpretAs, format: Option<&str>) -> Self {
+            self.push(Ok(VoiceSegment::say_as(text, interpret_as, format)))
+        }
+
+        pub fn phoneme(self, text: &str, alphabet: PhoneticAlphabet, ph: &str) -> Self {
+            self.push(Ok(VoiceSegment::phoneme(text, alphabet, ph)))
+        }
+
+        pub fn bookmark(self, mark: &str) -> Self {
+            self.push(Ok(VoiceSegment::bookmark(mark)))
+        }
+
+        pub fn style(
+            self,
+            expression: impl Into<Expression>,
+            build: impl FnOnce(SegmentBuilder) -> SegmentBuilder,
+        ) -> Self {
+            let expression = expression.into();
+            self.push(
+                build(SegmentBuilder::new())
+                    .finish()
+                    .map(|contents| VoiceSegment::express_as(expression, contents)),
+            )
+        }
+
+        pub fn emphasis(
+            self,
+            level: EmphasisLevel,
+            build: impl FnOnce(SegmentBuilder) -> SegmentBuilder,
+        ) -> Self {
+            self.push(
+                build(SegmentBuilder::new())
+                    .finish()
+                    .map(|contents| VoiceSegment::emphasis(level, contents)),
+            )
+        }
+
+        /// Text in another language
+        pub fn lang(
+            self,
+            language: &Locale,
+            build: impl FnOnce(SegmentBuilder) -> SegmentBuilder,
+        ) -> Self {
+            self.push(
+                build(SegmentBuilder::new())
+                    .finish()
+                    .map(|contents| VoiceSegment::lang(language, contents)),
+            )
+        }
+
+        pub fn prosody(
+            self,
+            settings: ProsodySettings,
+            build: impl FnOnce(SegmentBuilder) -> SegmentBuilder,
+        ) -> Self {
+            self.push(
+                build(SegmentBuilder::new())
+                    .finish()
+                    .map(|contents| VoiceSegment::prosody(settings, contents)),
+            )
+        }
+
+        pub fn paragraph(self, build: impl FnOnce(SegmentBuilder) -> SegmentBuilder) -> Self {
+            self.push(
+                build(SegmentBuilder::new())
+                    .finish()
+                    .map(VoiceSegment::paragraph),
+            )
+        }
+
+        pub fn sentence(self, build: impl FnOnce(SegmentBuilder) -> SegmentBuilder) -> Self {
+            self.push(
+                build(SegmentBuilder::new())
+                    .finish()
+                    .map(VoiceSegment::sentence),
+            )
+        }
+
+        /// Prerecorded audio with fallback content spoken if the audio can't be played
+        pub fn audio(
+            self,
+            src: &str,
+            build: impl FnOnce(SegmentBuilder) -> SegmentBuilder,
+        ) -> Self {
+            self.push(
+                build(SegmentBuilder::new())
+                    .finish()
+                    .and_then(|fallback| VoiceSegment::audio(src, fallback)),
+            )
+        }
+    };
+}
+
+impl Speak {
+    pub fn builder(voice_settings: &VoiceSettings) -> SpeakBuilder {
+        SpeakBuilder::new(voice_settings)
+    }
+}
+
+/// Builds a [`Speak`] document. Errors from individual segments are reported by [`SpeakBuilder::build`]
+pub struct SpeakBuilder {
+    language: Locale,
+    voices: Vec<Voice>,
+    voice_settings: VoiceSettings,
+    segments: SegmentBuilder,
+    lexicons: Vec<String>,
+    pronunciations: Option<PronunciationDictionary>,
+    background_audio: Option<Result<BackgroundAudio>>,
+}
+
+impl SpeakBuilder {
+    pub fn new(voice_settings: &VoiceSettings) -> Self {
+        Self {
+            language: voice_settings.language.clone(),
+            voices: vec![],
+            voice_settings: voice_settings.clone(),
+            segments: SegmentBuilder::new(),
+            lexicons: vec![],
+            pronunciations: None,
+            background_audio: None,
+        }
+    }
+
+    /// Switch to a different voice for the following segments
+    pub fn voice(mut self, voice_settings: &VoiceSettings) -> Self {
+        self.finish_voice();
+        self.voice_settings = voice_settings.clone();
+        self
+    }
+
+    pub fn lexicon(mut self, uri: &str) -> Self {
+        self.lexicons.push(uri.to_owned());
+        self
+    }
+
+    /// Dictionary applied to the text of all voices when the document is built
+    pub fn pronunciations(mut self, dictionary: PronunciationDictionary) -> Self {
+        self.pronunciations = Some(dictionary);
+        self
+    }
+
+    pub fn background_audio(mut self, background_audio: Result<BackgroundAudio>) -> Self {
+        self.background_audio = Some(background_audio);
+        self
+    }
+
+    /// Build and validate the document
+    pub fn build(mut self) -> Result<Speak> {
+        self.finish_voice();
+        if let Some(error) = self.segments.error {
+            return Err(error);
+        }
+        let mut speak = Speak::with_voices(&self.language, self.voices);
+        for lexicon in &self.lexicons {
+            speak = speak.with_lexicon(lexicon);
+        }
+        if let Some(dictionary) = &self.pronunciations {
+            speak = speak.with_pronunciations(dictionary);
+        }
+        if let Some(background_audio) = self.background_audio {
+            speak = speak.with_background_audio(background_audio?);
+        }
+        speak.validate()?;
+        Ok(speak)
+    }
+
+    segment_methods!();
+
+    fn push(mut self, segment: Result<VoiceSegment>) -> Self {
+        self.segments = self.segments.push(segment);
+        self
+    }
+
+    fn finish_voice(&mut self) {
+        let segments = std::mem::take(&mut self.segments.segments);
+        if !segments.is_empty() {
+            self.voices.push(Voice::new(&self.voice_settings, segments));
+        }
+    }
+}
+
+/// Builds nested content for elements such as express-as or emphasis
+#[derive(Default)]
+pub struct SegmentBuilder {
+    segments: Vec<VoiceSegment>,
+    error: Option<TtsError>,
+}
+
+impl SegmentBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    segment_methods!();
+
+    pub fn finish(self) -> Result<Vec<VoiceSegment>> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.segments),
+        }
+    }
+
+    fn push(mut self, segment: Result<VoiceSegment>) -> Self {
+        match segment {
+            Ok(segment) => self.segments.push(segment),
+            // keep the first error
+            Err(error) => {
+                if self.error.is_none() {
+                    self.error = Some(error);
+                }
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnUsVoices, Role, Style};
+
+    #[test]
+    fn builds_nested_document() {
+        let jenny = EnUsVoices::JennyNeural.to_voice_settings();
+        let speak = Speak::builder(&jenny)
+            .text("Hello ")
+            .pause(Duration::from_millis(500))
+            .style(Style::Cheerful, |b| {
+                b.text("great ")
+                    .emphasis(EmphasisLevel::Strong, |b| b.text("news"))
+            })
+            .build()
+            .unwrap();
+
+        let expected = Speak::segments_with_voice_settings(
+            &jenny,
+            vec![
+                VoiceSegment::plain("Hello "),
+                VoiceSegment::pause(Duration::from_millis(500)).unwrap(),
+                VoiceSegment::express_as(
+                    Style::Cheerful,
+                    vec![
+                        VoiceSegment::plain("great "),
+                        VoiceSegment::emphasis(
+                            EmphasisLevel::Strong,
+                            vec![VoiceSegment::plain("news")],
+                        ),
+                    ],
+                ),
+            ],
+        );
+        assert_eq!(expected, speak);
+    }
+
+    #[test]
+    fn builds_multiple_voices() {
+        let jenny = EnUsVoices::JennyNeural.to_voice_settings();
+        let guy = EnUsVoices::GuyNeural.to_voice_settings();
+        let expression = Expression::new(Style::Sad)
+            .with_style_degree(1.5)
+            .unwrap()
+            .with_role(Role::Boy);
+        let speak = Speak::builder(&jenny)
+            .text("Hi")
+            .voice(&guy)
+            .style(expression.clone(), |b| b.text("Hello"))
+            .lexicon("https://example.com/lexicon.xml")
+            .build()
+            .unwrap();
+
+        let expected = Speak::voices_with_voice_settings(vec![
+            (jenny, vec![VoiceSegment::plain("Hi")]),
+            (
+                guy,
+                vec![VoiceSegment::with_expression("Hello", expression)],
+            ),
+        ])
+        .with_lexicon("https://example.com/lexicon.xml");
+        assert_eq!(expected, speak);
+    }
+
+    #[test]
+    fn reports_nested_errors() {
+        let result = Speak::builder(&EnUsVoices::JennyNeural.to_voice_settings())
+            .text("Hello")
+            .paragraph(|b| b.sentence(|b| b.pause(Duration::from_secs(60))))
+            .build();
+        assert!(matches!(result, Err(TtsError::InvalidValue(_))));
+    }
+
+    #[test]
+    fn validates_document() {
+        let result = Speak::builder(&EnUsVoices::JennyNeural.to_voice_settings())
+            .prosody(ProsodySettings::new().with_rate("ludicrous"), |b| {
+                b.text("fast")
+            })
+            .build();
+        assert!(matches!(result, Err(TtsError::InvalidSsml(_))));
+    }
+}
@@ -0,0 +1,758 @@
+/// Parser reading ssml documents back into the [`Speak`] model.
+///
+/// Documents produced by [`Speak::to_ssml_xml`] parse back into an equal model.
+use crate::{
+    ssml_serializer::{
+        BackgroundAudio, Break, Expression, ProsodySettings, SayAs, Speak, Voice, VoiceSegment,
+    },
+    xml::parse_error,
+    BreakStrength, EmphasisLevel, Locale, PhoneticAlphabet, Result, Role, TtsError,
+};
+use quick_xml::{events::Event, Reader};
+use std::time::Duration;
+
+impl Speak {
+    /// Parse ssml including the mstts extensions.
+    ///
+    /// Errors point to the line and column of the offending element.
+    pub fn from_ssml_str(ssml: &str) -> Result<Self> {
+        SsmlParser::new(ssml).parse()
+    }
+}
+
+enum Node {
+    Element(Element),
+    Text(String),
+    End,
+    Eof,
+}
+
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    empty: bool,
+    position: usize,
+}
+
+impl Element {
+    fn take(&mut self, key: &str) -> Option<String> {
+        let index = self.attributes.iter().position(|(name, _)| name == key)?;
+        Some(self.attributes.remove(index).1)
+    }
+}
+
+struct SsmlParser<'a> {
+    source: &'a str,
+    reader: Reader<&'a [u8]>,
+    buf: Vec<u8>,
+}
+
+impl<'a> SsmlParser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            reader: Reader::from_str(source),
+            buf: Vec::new(),
+        }
+    }
+
+    fn parse(mut self) -> Result<Speak> {
+        let mut speak = None;
+        loop {
+            let (position, node) = self.next_node()?;
+            match node {
+                Node::Element(element) if element.name == "speak" && speak.is_none() => {
+                    speak = Some(self.parse_speak(element)?);
+                }
+                Node::Element(element) => {
+                    return Err(self.error(
+                        element.position,
+                        format!("unexpected element <{}>, expected <speak>", element.name),
+                    ))
+                }
+                Node::Text(text) if text.trim().is_empty() => (),
+                Node::Text(_) => {
+                    return Err(self.error(position, "text outside of <speak> element"))
+                }
+                Node::End => return Err(self.error(position, "unexpected end tag")),
+                Node::Eof => break,
+            }
+        }
+        speak.ok_or_else(|| self.error(self.source.len(), "missing <speak> element"))
+    }
+
+    fn parse_speak(&mut self, mut element: Element) -> Result<Speak> {
+        let xml_lang = self.required(&mut element, "xml:lang")?;
+        let xml_lang = self.parse_locale(element.position, &xml_lang)?;
+        element.take("version");
+        self.finish_attributes(element.position, element.attributes)?;
+        let mut speak = Speak::with_voices(&xml_lang, vec![]);
+        if element.empty {
+            return Ok(speak);
+        }
+        // lexicons outside of voices apply to every voice of the document
+        let mut lexicons = vec![];
+        loop {
+            let (position, node) = self.next_node()?;
+            match node {
+                Node::Element(child) => match child.name.as_str() {
+                    "voice" => {
+                        let voice = self.parse_voice(child)?;
+                        speak.voices.push(voice);
+                    }
+                    "lexicon" => lexicons.push(self.parse_lexicon(child)?),
+                    "mstts:backgroundaudio" => {
+                        speak.background_audio = Some(self.parse_background_audio(child)?);
+                    }
+                    _ => return Err(self.unsupported(&child)),
+                },
+                Node::Text(text) if text.trim().is_empty() => (),
+                Node::Text(_) => {
+                    return Err(self.error(position, "text outside of <voice> element"))
+                }
+                Node::End => {
+                    return Ok(lexicons
+                        .iter()
+                        .fold(speak, |speak, uri| speak.with_lexicon(uri)))
+                }
+                Node::Eof => return Err(self.error(position, "unexpected end of document")),
+            }
+        }
+    }
+
+    fn parse_voice(&mut self, mut element: Element) -> Result<Voice> {
+        let name = self.required(&mut element, "name")?;
+        let xml_lang = element
+            .take("xml:lang")
+            .map(|xml_lang| self.parse_locale(element.position, &xml_lang))
+            .transpose()?;
+        let xml_gender = element.take("xml:gender");
+        self.finish_attributes(element.position, element.attributes)?;
+        let mut voice = Voice {
+            xml_lang,
+            xml_gender,
+            name,
+            lexicons: vec![],
+            body: vec![],
+        };
+        if element.empty {
+            return Ok(voice);
+        }
+        loop {
+            let (position, node) = self.next_node()?;
+            match node {
+                // the service expects lexicons inside of voice elements
+                Node::Element(child) if child.name == "lexicon" => {
+                    let uri = self.parse_lexicon(child)?;
+                    voice = voice.with_lexicon(&uri);
+                }
+                Node::Element(child) => {
+                    let segment = self.parse_segment(child)?;
+                    voice.body.push(segment);
+                }
+                Node::Text(text) => push_text(&mut voice.body, text),
+                Node::End => return Ok(voice),
+                Node::Eof => return Err(self.error(position, "unexpected end of document")),
+            }
+        }
+    }
+
+    fn parse_lexicon(&mut self, mut element: Element) -> Result<String> {
+        let uri = self.required(&mut element, "uri")?;
+        self.finish_attributes(element.position, element.attributes)?;
+        self.expect_empty(element.empty)?;
+        Ok(uri)
+    }
+
+    fn parse_background_audio(&mut self, mut element: Element) -> Result<BackgroundAudio> {
+        let position = element.position;
+        let src = self.required(&mut element, "src")?;
+        let volume = element
+            .take("volume")
+            .map(|volume| self.parse_number(position, "volume", &volume))
+            .transpose()?;
+        let fade_in = element
+            .take("fadein")
+            .map(|fade| self.parse_milliseconds(position, "fadein", &fade))
+            .transpose()?;
+        let fade_out = element
+            .take("fadeout")
+            .map(|fade| self.parse_milliseconds(position, "fadeout", &fade))
+            .transpose()?;
+        self.finish_attributes(position, element.attributes)?;
+        self.expect_empty(element.empty)?;
+        let mut background_audio =
+            BackgroundAudio::new(&src).map_err(|error| self.invalid(position, error))?;
+        if let Some(volume) = volume {
+            background_audio = background_audio
+                .with_volume(volume)
+                .map_err(|error| self.invalid(position, error))?;
+        }
+        if let Some(fade_in) = fade_in {
+            background_audio = background_audio
+                .with_fade_in(fade_in)
+                .map_err(|error| self.invalid(position, error))?;
+        }
+        if let Some(fade_out) = fade_out {
+            background_audio = background_audio
+                .with_fade_out(fade_out)
+                .map_err(|error| self.invalid(position, error))?;
+        }
+        Ok(background_audio)
+    }
+
+    fn parse_segment(&mut self, mut element: Element) -> Result<VoiceSegment> {
+        let position = element.position;
+        let segment = match element.name.as_str() {
+            "mstts:express-as" => {
+                let style = self.required(&mut element, "style")?;
+                let style_degree = element
+                    .take("styledegree")
+                    .map(|degree| self.parse_number(position, "styledegree", &degree))
+                    .transpose()?;
+                let role = element
+                    .take("role")
+                    .map(|role| {
+                        role.parse::<Role>()
+                            .map_err(|error| self.invalid(position, error))
+                    })
+                    .transpose()?;
+                self.finish_attributes(position, element.attributes)?;
+                let mut expression =
+                    Expression::new(style.parse().unwrap_or_else(|never| match never {}));
+                if let Some(style_degree) = style_degree {
+                    expression = expression
+                        .with_style_degree(style_degree)
+                        .map_err(|error| self.invalid(position, error))?;
+                }
+                if let Some(role) = role {
+                    expression = expression.with_role(role);
+                }
+                VoiceSegment::express_as(expression, self.parse_children(element.empty)?)
+            }
+            "mstts:silence" => {
+                let attribute_type = self.required(&mut element, "type")?;
+                let value = self.required(&mut element, "value")?;
+                let value = self.parse_time(position, "value", &value)?;
+                self.finish_attributes(position, element.attributes)?;
+                self.expect_empty(element.empty)?;
+                let attribute_type = attribute_type
+                    .parse()
+                    .unwrap_or_else(|never| match never {});
+                VoiceSegment::silence(attribute_type, value)
+                    .map_err(|error| self.invalid(position, error))?
+            }
+            "sub" => {
+                let alias = self.required(&mut element, "alias")?;
+                self.finish_attributes(position, element.attributes)?;
+                VoiceSegment::sub(&self.parse_text(element.empty)?, &alias)
+            }
+            "emphasis" => {
+                let level = element
+                    .take("level")
+                    .map(|level| {
+                        level
+                            .parse::<EmphasisLevel>()
+                            .map_err(|error| self.invalid(position, error))
+                    })
+                    .transpose()?
+                    .unwrap_or(EmphasisLevel::Moderate);
+                self.finish_attributes(position, element.attributes)?;
+                VoiceSegment::emphasis(level, self.parse_children(element.empty)?)
+            }
+            "lang" => {
+                let xml_lang = self.required(&mut element, "xml:lang")?;
+                let xml_lang = self.parse_locale(position, &xml_lang)?;
+                self.finish_attributes(position, element.attributes)?;
+                VoiceSegment::lang(&xml_lang, self.parse_children(element.empty)?)
+            }
+            "p" => {
+                self.finish_attributes(position, element.attributes)?;
+                VoiceSegment::paragraph(self.parse_children(element.empty)?)
+            }
+            "s" => {
+                self.finish_attributes(position, element.attributes)?;
+                VoiceSegment::sentence(self.parse_children(element.empty)?)
+            }
+            "audio" => {
+                let src = self.required(&mut element, "src")?;
+                self.finish_attributes(position, element.attributes)?;
+                VoiceSegment::audio(&src, self.parse_children(element.empty)?)
+                    .map_err(|error| self.invalid(position, error))?
+            }
+            "break" => {
+                let strength = element
+                    .take("strength")
+                    .map(|strength| {
+                        strength
+                            .parse::<BreakStrength>()
+                            .map_err(|error| self.invalid(position, error))
+                    })
+                    .transpose()?;
+                let time = element
+                    .take("time")
+                    .map(|time| self.parse_time(position, "time", &time))
+                    .transpose()?;
+                self.finish_attributes(position, element.attributes)?;
+                self.expect_empty(element.empty)?;
+                let pause =
+                    Break::new(strength, time).map_err(|error| self.invalid(position, error))?;
+                VoiceSegment::Break(pause)
+            }
+            "prosody" => {
+                let settings = ProsodySettings {
+                    rate: element.take("rate"),
+                    pitch: element.take("pitch"),
+                    volume: element.take("volume"),
+                    contour: element.take("contour"),
+                };
+                self.finish_attributes(position, element.attributes)?;
+                VoiceSegment::prosody(settings, self.parse_children(element.empty)?)
+            }
+            "say-as" => {
+                let interpret_as = self.required(&mut element, "interpret-as")?;
+                let format = element.take("format");
+                let detail = element.take("detail");
+                self.finish_attributes(position, element.attributes)?;
+                VoiceSegment::SayAs(SayAs {
+                    interpret_as: interpret_as.parse().unwrap_or_else(|never| match never {}),
+                    format,
+                    detail,
+                    body: self.parse_text(element.empty)?,
+                })
+            }
+            "phoneme" => {
+                let alphabet = self.required(&mut element, "alphabet")?;
+                let alphabet = alphabet
+                    .parse::<PhoneticAlphabet>()
+                    .map_err(|error| self.invalid(position, error))?;
+                let ph = self.required(&mut element, "ph")?;
+                self.finish_attributes(position, element.attributes)?;
+                VoiceSegment::phoneme(&self.parse_text(element.empty)?, alphabet, &ph)
+            }
+            "bookmark" => {
+                let mark = self.required(&mut element, "mark")?;
+                self.finish_attributes(position, element.attributes)?;
+                self.expect_empty(element.empty)?;
+                VoiceSegment::bookmark(&mark)
+            }
+            _ => return Err(self.unsupported(&element)),
+        };
+        Ok(segment)
+    }
+
+    fn parse_children(&mut self, empty: bool) -> Result<Vec<VoiceSegment>> {
+        let mut segments = vec![];
+        if empty {
+            return Ok(segments);
+        }
+        loop {
+            let (position, node) = self.next_node()?;
+            match node {
+                Node::Element(child) => {
+                    let segment = self.parse_segment(child)?;
+                    segments.push(segment);
+                }
+                Node::Text(text) => push_text(&mut segments, text),
+                Node::End => return Ok(segments),
+                Node::Eof => return Err(self.error(position, "unexpected end of document")),
+            }
+        }
+    }
+
+    /// Content of elements that only allow text such as sub and say-as
+    fn parse_text(&mut self, empty: bool) -> Result<String> {
+        let mut text = String::new();
+        if empty {
+            return Ok(text);
+        }
+        loop {
+            let (position, node) = self.next_node()?;
+            match node {
+                Node::Text(value) => text.push_str(&value),
+                Node::Element(child) => {
+                    return Err(self.error(
+                        child.position,
+                        format!("unexpected element <{}>, only text is allowed", child.name),
+                    ))
+                }
+                Node::End => return Ok(text),
+                Node::Eof => return Err(self.error(position, "unexpected end of document")),
+            }
+        }
+    }
+
+    fn expect_empty(&mut self, empty: bool) -> Result<()> {
+        let text = self.parse_text(empty)?;
+        if !text.trim().is_empty() {
+            return Err(self.error(self.reader.buffer_position(), "element must be empty"));
+        }
+        Ok(())
+    }
+
+    fn next_node(&mut self) -> Result<(usize, Node)> {
+        loop {
+            let position = self.reader.buffer_position();
+            self.buf.clear();
+            let event = self
+                .reader
+                .read_event(&mut self.buf)
+                .map_err(|e| parse_error(self.source, self.reader.buffer_position(), e))?;
+            let node = match event {
+                Event::Start(ref start) | Event::Empty(ref start) => {
+                    let mut attributes = vec![];
+                    for attribute in start.attributes() {
+                        let attribute =
+                            attribute.map_err(|e| parse_error(self.source, position, e))?;
+                        let value = attribute
+                            .unescape_and_decode_value(&self.reader)
+                            .map_err(|e| parse_error(self.source, position, e))?;
+                        attributes
+                            .push((String::from_utf8_lossy(attribute.key).into_owned(), value));
+                    }
+                    Node::Element(Element {
+                        name: String::from_utf8_lossy(start.name()).into_owned(),
+                        attributes,
+                        empty: matches!(event, Event::Empty(_)),
+                        position,
+                    })
+                }
+                Event::Text(ref text) => Node::Text(
+                    text.unescape_and_decode(&self.reader)
+                        .map_err(|e| parse_error(self.source, position, e))?,
+                ),
+                Event::CData(ref text) => Node::Text(String::from_utf8_lossy(text).into_owned()),
+                Event::End(_) => Node::End,
+                Event::Eof => Node::Eof,
+                // declarations, comments and processing instructions carry no content
+                _ => continue,
+            };
+            return Ok((position, node));
+        }
+    }
+
+    fn required(&self, element: &mut Element, key: &str) -> Result<String> {
+        element.take(key).ok_or_else(|| {
+            self.error(
+                element.position,
+                format!("missing {} attribute on <{}>", key, element.name),
+            )
+        })
+    }
+
+    fn finish_attributes(&self, position: usize, attributes: Vec<(String, String)>) -> Result<()> {
+        match attributes
+            .iter()
+            .find(|(key, _)| key != "xmlns" && !key.starts_with("xmlns:"))
+        {
+            Some((key, _)) => Err(self.error(position, format!("unsupported attribute {}", key))),
+            None => Ok(()),
+        }
+    }
+
+    fn parse_number(&self, position: usize, key: &str, value: &str) -> Result<f32> {
+        value
+            .trim()
+            .parse()
+            .map_err(|_| self.error(position, format!("invalid {} {:?}", key, value)))
+    }
+
+    fn parse_locale(&self, position: usize, value: &str) -> Result<Locale> {
+        value
+            .parse()
+            .map_err(|_| self.error(position, format!("invalid xml:lang {:?}", value)))
+    }
+
+    fn parse_milliseconds(&self, position: usize, key: &str, value: &str) -> Result<Duration> {
+        value
+            .trim()
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| self.error(position, format!("invalid {} {:?}", key, value)))
+    }
+
+    fn parse_time(&self, position: usize, key: &str, value: &str) -> Result<Duration> {
+        parse_time(value)
+            .ok_or_else(|| self.error(position, format!("invalid {} {:?}", key, value)))
+    }
+
+    /// Error of a model constructor, located at the element
+    fn invalid(&self, position: usize, error: TtsError) -> TtsError {
+        let message = match error {
+            TtsError::InvalidValue(message) => message,
+            error => error.to_string(),
+        };
+        self.error(position, message)
+    }
+
+    fn unsupported(&self, element: &Element) -> TtsError {
+        self.error(
+            element.position,
+            format!("unsupported element <{}>", element.name),
+        )
+    }
+
+    fn error(&self, position: usize, message: impl ToString) -> TtsError {
+        parse_error(self.source, position, message)
+    }
+}
+
+/// Parse ssml time values such as `500ms` or `1.5s`
+pub(crate) fn parse_time(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let milliseconds = if let Some(milliseconds) = value.strip_suffix("ms") {
+        milliseconds.trim().parse::<f64>().ok()?
+    } else {
+        value.strip_suffix('s')?.trim().parse::<f64>().ok()? * 1000.0
+    };
+    if !milliseconds.is_finite() || milliseconds < 0.0 {
+        return None;
+    }
+    Some(Duration::from_nanos(
+        (milliseconds * 1_000_000.0).round() as u64
+    ))
+}
+
+pub(crate) fn push_text(segments: &mut Vec<VoiceSegment>, text: String) {
+    if let Some(VoiceSegment::Plain(previous)) = segments.last_mut() {
+        previous.push_str(&text);
+    } else if !text.is_empty() {
+        segments.push(VoiceSegment::Plain(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnUsVoices, InterpretAs, SilenceAttributeType, Style, VoiceGender};
+
+    #[test]
+    fn parse_simple_document() {
+        let ssml = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+lorem ipsum\
+</voice>\
+</speak>";
+        let speak = Speak::from_ssml_str(ssml).unwrap();
+        let expected = Speak::with_text(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyNeural",
+            "lorem ipsum",
+        );
+        assert_eq!(expected, speak);
+    }
+
+    #[test]
+    fn parse_hand_written_document() {
+        let ssml = r#"<?xml version="1.0"?>
+<!-- greeting -->
+<speak version="1.0" xmlns="http://www.w3.org/2001/10/synthesis"
+    xmlns:mstts="https://www.w3.org/2001/mstts" xml:lang="en-US">
+    <voice name="en-US-SaraNeural">
+        <mstts:express-as style="cheerful" styledegree="2">Hi &amp; welcome</mstts:express-as>
+        <break time="1.5s"/>
+        <mstts:silence type="Tailing-exact" value="200ms"/>
+    </voice>
+</speak>"#;
+        let speak = Speak::from_ssml_str(ssml).unwrap();
+        assert_eq!(1, speak.voices().len());
+        let voice = &speak.voices()[0];
+        assert_eq!("en-US-SaraNeural", voice.name());
+        assert_eq!(None, voice.xml_lang);
+        let expression = Expression::new(Style::Cheerful)
+            .with_style_degree(2.0)
+            .unwrap();
+        let expected = vec![
+            VoiceSegment::plain("\n        "),
+            VoiceSegment::with_expression("Hi & welcome", expression),
+            VoiceSegment::plain("\n        "),
+            VoiceSegment::pause(Duration::from_millis(1500)).unwrap(),
+            VoiceSegment::plain("\n        "),
+            VoiceSegment::silence(
+                SilenceAttributeType::TailingExact,
+                Duration::from_millis(200),
+            )
+            .unwrap(),
+            VoiceSegment::plain("\n    "),
+        ];
+        assert_eq!(expected, voice.segments());
+    }
+
+    #[test]
+    fn round_trip() {
+        let background_audio = BackgroundAudio::new("https://example.com/music.wav")
+            .unwrap()
+            .with_volume(0.5)
+            .unwrap()
+            .with_fade_in(Duration::from_millis(1200))
+            .unwrap();
+        let expression = Expression::new(Style::Other("singing".to_owned()))
+            .with_style_degree(0.5)
+            .unwrap()
+            .with_role(Role::Girl);
+        let speak = Speak::voices_with_voice_settings(vec![
+            (
+                EnUsVoices::JennyMultilingualNeural.to_voice_settings(),
+                vec![
+                    VoiceSegment::bookmark("start"),
+                    VoiceSegment::paragraph(vec![
+                        VoiceSegment::sentence(vec![
+                            VoiceSegment::plain("The "),
+                            VoiceSegment::sub("W3C", "World Wide Web Consortium"),
+                            VoiceSegment::plain(" says <hi>"),
+                        ]),
+                        VoiceSegment::sentence(vec![VoiceSegment::lang(
+                            &"de-DE".parse().unwrap(),
+                            vec![VoiceSegment::emphasis(
+                                EmphasisLevel::Reduced,
+                                vec![VoiceSegment::plain("Hallo")],
+                            )],
+                        )]),
+                    ]),
+                    VoiceSegment::silence(
+                        SilenceAttributeType::Sentenceboundary,
+                        Duration::from_millis(300),
+                    )
+                    .unwrap(),
+                    VoiceSegment::audio(
+                        "https://example.com/jingle.wav",
+                        vec![VoiceSegment::plain("jingle")],
+                    )
+                    .unwrap(),
+                ],
+            ),
+            (
+                EnUsVoices::GuyNeural.to_voice_settings(),
+                vec![
+                    VoiceSegment::express_as(
+                        expression,
+                        vec![VoiceSegment::prosody(
+                            ProsodySettings::new().with_rate("slow").with_volume("+20%"),
+                            vec![VoiceSegment::plain("quietly")],
+                        )],
+                    ),
+                    VoiceSegment::pause_with_strength(BreakStrength::Weak),
+                    VoiceSegment::say_as("2026-10-19", InterpretAs::Date, Some("ymd")),
+                    VoiceSegment::phoneme("tomato", PhoneticAlphabet::Ipa, "təˈmeɪtoʊ"),
+                ],
+            ),
+        ])
+        .with_lexicon("https://example.com/lexicon.xml")
+        .with_background_audio(background_audio);
+
+        let ssml = speak.to_ssml_xml();
+        let parsed = Speak::from_ssml_str(&ssml).unwrap();
+        assert_eq!(speak, parsed);
+        assert_eq!(ssml, parsed.to_ssml_xml());
+    }
+
+    #[test]
+    fn round_trip_voice_lexicons_and_fractional_times() {
+        let speak = Speak::with_voices(
+            &Locale::en_us(),
+            vec![
+                Voice::with_text(&EnUsVoices::JennyNeural.to_voice_settings(), "Hi"),
+                Voice::new(
+                    &EnUsVoices::GuyNeural.to_voice_settings(),
+                    vec![
+                        VoiceSegment::pause(Duration::from_micros(1500)).unwrap(),
+                        VoiceSegment::silence(
+                            SilenceAttributeType::Leading,
+                            Duration::from_micros(250),
+                        )
+                        .unwrap(),
+                    ],
+                )
+                .with_lexicon("https://example.com/lexicon.xml"),
+            ],
+        );
+
+        let ssml = speak.to_ssml_xml();
+        assert_eq!(1, ssml.matches("<lexicon").count());
+        assert!(ssml.contains("<break time=\"1.5ms\"/>"));
+        assert!(ssml.contains("value=\"0.25ms\""));
+        let parsed = Speak::from_ssml_str(&ssml).unwrap();
+        assert_eq!(speak, parsed);
+        assert_eq!(ssml, parsed.to_ssml_xml());
+    }
+
+    #[test]
+    fn error_for_values_out_of_range() {
+        for (element, expected) in [
+            ("<break time=\"7s\"/>", "break 7000ms is longer than 5000ms"),
+            (
+                "<mstts:silence type=\"Leading\" value=\"6s\"/>",
+                "silence 6000ms is longer than 5000ms",
+            ),
+            (
+                "<audio src=\"http://example.com/a.wav\"/>",
+                "invalid url \"http://example.com/a.wav\"",
+            ),
+            (
+                "<mstts:express-as style=\"sad\" styledegree=\"3\">x</mstts:express-as>",
+                "style degree 3 is outside of 0.01 to 2",
+            ),
+        ] {
+            let ssml = format!(
+                "<speak xml:lang=\"en-US\"><voice name=\"a\">{}</voice></speak>",
+                element
+            );
+            match Speak::from_ssml_str(&ssml) {
+                Err(TtsError::XmlParseError {
+                    line,
+                    column,
+                    message,
+                }) => assert_eq!((1, 41, expected), (line, column, message.as_str())),
+                other => panic!("unexpected result {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn error_position_for_unknown_element() {
+        let ssml = "<speak xml:lang=\"en-US\">\n  <voice name=\"en-US-JennyNeural\">\n    hello <foo/>\n  </voice>\n</speak>";
+        match Speak::from_ssml_str(ssml) {
+            Err(TtsError::XmlParseError {
+                line,
+                column,
+                message,
+            }) => {
+                assert_eq!(3, line);
+                assert_eq!(11, column);
+                assert_eq!("unsupported element <foo>", message);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn error_for_missing_attribute() {
+        let ssml = "<speak xml:lang=\"en-US\"><voice><break/></voice></speak>";
+        match Speak::from_ssml_str(ssml) {
+            Err(TtsError::XmlParseError { line, column, .. }) => {
+                assert_eq!(1, line);
+                assert_eq!(25, column);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn error_for_mismatched_tags() {
+        let ssml = "<speak xml:lang=\"en-US\"><voice name=\"a\"><s>text</p></voice></speak>";
+        assert!(matches!(
+            Speak::from_ssml_str(ssml),
+            Err(TtsError::XmlParseError { .. })
+        ));
+    }
+
+    #[test]
+    fn time_parsing() {
+        assert_eq!(Some(Duration::from_millis(500)), parse_time("500ms"));
+        assert_eq!(Some(Duration::from_millis(1500)), parse_time("1.5s"));
+        assert_eq!(None, parse_time("-1s"));
+        assert_eq!(None, parse_time("fast"));
+    }
+}
@@ -1,115 +1,220 @@
-/// Very simple ssml serializer. Currently only supports single voice selection.
-use crate::{types::VoiceGender, SilenceAttributeType, Style, VoiceSettings};
-use serde::Serialize;
+/// Very simple ssml serializer.
+use crate::{
+    types::VoiceGender, xml::XmlWriter, BreakStrength, EmphasisLevel, InterpretAs, Locale,
+    PhoneticAlphabet, Result, Role, SentenceSegmenter, SilenceAttributeType, Style, TtsError,
+    VoiceSettings,
+};
+use std::time::Duration;
 
 const XML_VERSION: &str = "1.0";
 const XMLNS_LINK: &str = "http://www.w3.org/2001/10/synthesis";
 const XMLNS_MSTTS_LINK: &str = "https://www.w3.org/2001/mstts";
+pub(crate) const MAX_BACKGROUND_AUDIO_FADE: Duration = Duration::from_secs(10);
+pub(crate) const MAX_SILENCE: Duration = Duration::from_millis(5000);
+pub(crate) const MAX_BREAK: Duration = Duration::from_millis(5000);
+pub(crate) const MIN_STYLE_DEGREE: f32 = 0.01;
+pub(crate) const MAX_STYLE_DEGREE: f32 = 2.0;
 
-#[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "speak")]
+#[derive(Debug, PartialEq)]
 pub struct Speak {
-    // Needs decimal numbers
-    version: &'static str,
-    xmlns: &'static str,
-    #[serde(rename = "xmlns:mstts")]
-    xmlns_mstts: &'static str,
-    #[serde(rename = "xml:lang")]
-    xml_lang: String,
-    voice: Voice,
+    pub(crate) xml_lang: Locale,
+    pub(crate) background_audio: Option<BackgroundAudio>,
+    pub(crate) voices: Vec<Voice>,
 }
 
 impl Speak {
-    pub fn new(language: &str, voice: Voice) -> Self {
+    pub fn new(language: &Locale, voice: Voice) -> Self {
+        Self::with_voices(language, vec![voice])
+    }
+
+    /// Multiple voices spoken one after another, for example a dialogue
+    pub fn with_voices(language: &Locale, voices: Vec<Voice>) -> Self {
         Self {
-            version: XML_VERSION,
-            xmlns: XMLNS_LINK,
-            xmlns_mstts: XMLNS_MSTTS_LINK,
-            xml_lang: language.to_owned(),
-            voice,
+            xml_lang: language.clone(),
+            background_audio: None,
+            voices,
         }
     }
 
-    pub fn with_text(language: &str, gender: VoiceGender, voice_name: &str, text: &str) -> Self {
+    pub fn with_text(language: &Locale, gender: VoiceGender, voice_name: &str, text: &str) -> Self {
         let voice = Voice {
-            xml_lang: language.to_owned(),
-            xml_gender: gender.as_string().to_owned(),
+            xml_lang: Some(language.clone()),
+            xml_gender: gender.xml_gender(),
             name: voice_name.to_owned(),
+            lexicons: vec![],
             body: vec![VoiceSegment::Plain(text.to_owned())],
         };
         Self::new(language, voice)
     }
 
     pub fn with_segments(
-        language: &str,
+        language: &Locale,
         gender: VoiceGender,
         voice_name: &str,
         contents: Vec<VoiceSegment>,
     ) -> Self {
         let voice = Voice {
-            xml_lang: language.to_owned(),
-            xml_gender: gender.as_string().to_owned(),
+            xml_lang: Some(language.clone()),
+            xml_gender: gender.xml_gender(),
             name: voice_name.to_owned(),
+            lexicons: vec![],
             body: contents,
         };
         Self::new(language, voice)
     }
 
     pub fn text_with_voice_settings(voice_settings: &VoiceSettings, text: &str) -> Self {
-        let voice = Voice {
-            xml_lang: voice_settings.language.to_owned(),
-            xml_gender: voice_settings.gender.as_string().to_owned(),
-            name: voice_settings.name.to_owned(),
-            body: vec![VoiceSegment::Plain(text.to_owned())],
-        };
-        Speak::new(&voice_settings.language.to_owned(), voice)
+        let voice = Voice::with_text(voice_settings, text);
+        Speak::new(&voice_settings.language, voice)
     }
 
     pub fn segments_with_voice_settings(
         voice_settings: &VoiceSettings,
         contents: Vec<VoiceSegment>,
     ) -> Self {
-        let voice = Voice {
-            xml_lang: voice_settings.language.to_owned(),
-            xml_gender: voice_settings.gender.as_string().to_owned(),
-            name: voice_settings.name.to_owned(),
-            body: contents,
-        };
-        Speak::new(&voice_settings.language.to_owned(), voice)
+        let voice = Voice::new(voice_settings, contents);
+        Speak::new(&voice_settings.language, voice)
+    }
+
+    /// Document language is taken from the first voice, `en-US` without voices
+    ///
+    /// A document without voices can't be synthesized, [`Speak::validate`] reports it.
+    pub fn voices_with_voice_settings(voices: Vec<(VoiceSettings, Vec<VoiceSegment>)>) -> Self {
+        let language = voices
+            .first()
+            .map(|(voice_settings, _)| voice_settings.language.clone())
+            .unwrap_or_else(Locale::en_us);
+        let voices = voices
+            .into_iter()
+            .map(|(voice_settings, contents)| Voice::new(&voice_settings, contents))
+            .collect();
+        Speak::with_voices(&language, voices)
+    }
+
+    pub fn voices(&self) -> &[Voice] {
+        &self.voices
+    }
+
+    /// Reference an external lexicon document such as one produced by [`crate::Lexicon::to_pls_xml`]
+    ///
+    /// The service expects lexicons inside of voice elements so the reference is added to every voice.
+    /// Use [`Voice::with_lexicon`] for a single voice.
+    pub fn with_lexicon(mut self, uri: &str) -> Self {
+        self.voices = self
+            .voices
+            .into_iter()
+            .map(|voice| voice.with_lexicon(uri))
+            .collect();
+        self
+    }
+
+    pub fn with_background_audio(mut self, background_audio: BackgroundAudio) -> Self {
+        self.background_audio = Some(background_audio);
+        self
     }
 
     pub fn to_ssml_xml(&self) -> String {
-        let xml = quick_xml::se::to_string(self).expect("Failed to serialize ssml");
-        // TODO(David): This is a weird hack
-        // Either there is a bug in the library
-        // Or more likely in my code
+        let mut writer = XmlWriter::new();
+        self.write(&mut writer);
+        writer.into_string()
+    }
 
-        // and it's getting even weirder now that I support the silence type
-        xml.replace("$value style", "mstts:express-as style")
-            .replace("$value type", "mstts:silence type")
-            .replace("$value", "mstts:express-as")
+    fn write(&self, writer: &mut XmlWriter) {
+        writer.start(
+            "speak",
+            &[
+                ("version", XML_VERSION),
+                ("xmlns", XMLNS_LINK),
+                ("xmlns:mstts", XMLNS_MSTTS_LINK),
+                ("xml:lang", self.xml_lang.as_str()),
+            ],
+        );
+        if let Some(background_audio) = &self.background_audio {
+            background_audio.write(writer);
+        }
+        for voice in &self.voices {
+            voice.write(writer);
+        }
+        writer.end("speak");
     }
 }
 
-#[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "voice")]
+#[derive(Debug, PartialEq)]
 pub struct Voice {
-    #[serde(rename = "xml:lang")]
-    xml_lang: String,
-    #[serde(rename = "xml:gender")]
-    xml_gender: String,
-    name: String,
-    #[serde(rename = "$value")]
-    body: Vec<VoiceSegment>,
+    pub(crate) xml_lang: Option<Locale>,
+    pub(crate) xml_gender: Option<String>,
+    pub(crate) name: String,
+    pub(crate) lexicons: Vec<String>,
+    pub(crate) body: Vec<VoiceSegment>,
+}
+
+impl Voice {
+    pub fn new(voice_settings: &VoiceSettings, contents: Vec<VoiceSegment>) -> Self {
+        Self {
+            xml_lang: Some(voice_settings.language.clone()),
+            xml_gender: voice_settings.gender.xml_gender(),
+            name: voice_settings.name.to_owned(),
+            lexicons: vec![],
+            body: contents,
+        }
+    }
+
+    pub fn with_text(voice_settings: &VoiceSettings, text: &str) -> Self {
+        Self::new(voice_settings, vec![VoiceSegment::plain(text)])
+    }
+
+    /// Reference an external lexicon document for this voice only
+    pub fn with_lexicon(mut self, uri: &str) -> Self {
+        if !self.lexicons.iter().any(|lexicon| lexicon == uri) {
+            self.lexicons.push(uri.to_owned());
+        }
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn segments(&self) -> &[VoiceSegment] {
+        &self.body
+    }
+
+    fn write(&self, writer: &mut XmlWriter) {
+        let mut attributes = vec![];
+        if let Some(xml_lang) = &self.xml_lang {
+            attributes.push(("xml:lang", xml_lang.as_str()));
+        }
+        if let Some(xml_gender) = &self.xml_gender {
+            attributes.push(("xml:gender", xml_gender.as_str()));
+        }
+        attributes.push(("name", &self.name));
+        writer.start("voice", &attributes);
+        for lexicon in &self.lexicons {
+            writer.empty("lexicon", &[("uri", lexicon)]);
+        }
+        for segment in &self.body {
+            segment.write(writer);
+        }
+        writer.end("voice");
+    }
 }
 
-#[derive(Debug, Serialize, PartialEq)]
-#[serde(untagged)]
+#[derive(Debug, PartialEq)]
 pub enum VoiceSegment {
     Plain(String),
-    #[serde(rename = "$value")]
     ExpressAs(ExpressAs),
     SilenceAttribute(SilenceAttribute),
+    Sub(Sub),
+    Emphasis(Emphasis),
+    Lang(Lang),
+    Paragraph(Paragraph),
+    Sentence(Sentence),
+    Audio(Audio),
+    Break(Break),
+    Prosody(Prosody),
+    SayAs(SayAs),
+    Phoneme(Phoneme),
+    Bookmark(Bookmark),
 }
 
 impl VoiceSegment {
@@ -117,49 +222,608 @@ impl VoiceSegment {
         VoiceSegment::Plain(text.to_owned())
     }
 
-    pub fn with_expression(text: &str, style: Style) -> Self {
+    /// Speak text in a given style. Accepts either a [`Style`] or an [`Expression`] with style degree and role
+    pub fn with_expression(text: &str, expression: impl Into<Expression>) -> Self {
+        Self::express_as(expression, vec![VoiceSegment::plain(text)])
+    }
+
+    pub fn express_as(expression: impl Into<Expression>, contents: Vec<VoiceSegment>) -> Self {
         let express_as = ExpressAs {
-            style,
-            body: text.to_owned(),
+            expression: expression.into(),
+            body: contents,
         };
         VoiceSegment::ExpressAs(express_as)
     }
 
-    pub fn silence(attribute_type: SilenceAttributeType, value: String) -> Self {
+    /// Silence of up to 5 seconds
+    pub fn silence(attribute_type: SilenceAttributeType, value: Duration) -> Result<Self> {
+        if value > MAX_SILENCE {
+            return Err(TtsError::InvalidValue(format!(
+                "silence {}ms is longer than {}ms",
+                value.as_millis(),
+                MAX_SILENCE.as_millis()
+            )));
+        }
         let silence = SilenceAttribute {
             attribute_type,
             value,
         };
-        VoiceSegment::SilenceAttribute(silence)
+        Ok(VoiceSegment::SilenceAttribute(silence))
+    }
+
+    /// Speak `alias` in place of `text`, useful for abbreviations
+    pub fn sub(text: &str, alias: &str) -> Self {
+        let sub = Sub {
+            alias: alias.to_owned(),
+            body: text.to_owned(),
+        };
+        VoiceSegment::Sub(sub)
+    }
+
+    pub fn emphasis(level: EmphasisLevel, contents: Vec<VoiceSegment>) -> Self {
+        let emphasis = Emphasis {
+            level,
+            body: contents,
+        };
+        VoiceSegment::Emphasis(emphasis)
+    }
+
+    /// Switch language for multilingual voices such as `en-US-JennyMultilingualNeural`
+    pub fn lang(language: &Locale, contents: Vec<VoiceSegment>) -> Self {
+        let lang = Lang {
+            xml_lang: language.clone(),
+            body: contents,
+        };
+        VoiceSegment::Lang(lang)
+    }
+
+    pub fn paragraph(contents: Vec<VoiceSegment>) -> Self {
+        VoiceSegment::Paragraph(Paragraph { body: contents })
+    }
+
+    pub fn sentence(contents: Vec<VoiceSegment>) -> Self {
+        VoiceSegment::Sentence(Sentence { body: contents })
+    }
+
+    /// Play prerecorded audio from an https `src`. `fallback` is spoken if the audio can't be played
+    pub fn audio(src: &str, fallback: Vec<VoiceSegment>) -> Result<Self> {
+        validate_audio_url(src)?;
+        let audio = Audio {
+            src: src.to_owned(),
+            body: fallback,
+        };
+        Ok(VoiceSegment::Audio(audio))
+    }
+
+    /// Pause of up to 5 seconds
+    pub fn pause(time: Duration) -> Result<Self> {
+        Break::new(None, Some(time)).map(VoiceSegment::Break)
+    }
+
+    pub fn pause_with_strength(strength: BreakStrength) -> Self {
+        let pause = Break {
+            strength: Some(strength),
+            time: None,
+        };
+        VoiceSegment::Break(pause)
+    }
+
+    pub fn prosody(settings: ProsodySettings, contents: Vec<VoiceSegment>) -> Self {
+        let prosody = Prosody {
+            settings,
+            body: contents,
+        };
+        VoiceSegment::Prosody(prosody)
+    }
+
+    pub fn say_as(text: &str, interpret_as: InterpretAs, format: Option<&str>) -> Self {
+        let say_as = SayAs {
+            interpret_as,
+            format: format.map(str::to_owned),
+            detail: None,
+            body: text.to_owned(),
+        };
+        VoiceSegment::SayAs(say_as)
+    }
+
+    pub fn phoneme(text: &str, alphabet: PhoneticAlphabet, ph: &str) -> Self {
+        let phoneme = Phoneme {
+            alphabet,
+            ph: ph.to_owned(),
+            body: text.to_owned(),
+        };
+        VoiceSegment::Phoneme(phoneme)
+    }
+
+    pub fn bookmark(mark: &str) -> Self {
+        VoiceSegment::Bookmark(Bookmark {
+            mark: mark.to_owned(),
+        })
+    }
+
+    /// Split plain text into paragraphs on blank lines and each paragraph into sentences
+    pub fn paragraphs(text: &str) -> Vec<VoiceSegment> {
+        let mut paragraphs = vec![];
+        let mut lines: Vec<&str> = vec![];
+        for line in text.lines().chain(std::iter::once("")) {
+            if line.trim().is_empty() {
+                if !lines.is_empty() {
+                    let paragraph_text = lines.join(" ");
+                    let sentences = SentenceSegmenter::default()
+                        .sentences(&paragraph_text)
+                        .into_iter()
+                        .map(|sentence| VoiceSegment::sentence(vec![VoiceSegment::plain(sentence)]))
+                        .collect();
+                    paragraphs.push(VoiceSegment::paragraph(sentences));
+                    lines.clear();
+                }
+            } else {
+                lines.push(line.trim());
+            }
+        }
+        paragraphs
+    }
+
+    fn write(&self, writer: &mut XmlWriter) {
+        match self {
+            VoiceSegment::Plain(text) => writer.text(text),
+            VoiceSegment::ExpressAs(express_as) => express_as.write(writer),
+            VoiceSegment::SilenceAttribute(silence) => silence.write(writer),
+            VoiceSegment::Sub(sub) => sub.write(writer),
+            VoiceSegment::Emphasis(emphasis) => emphasis.write(writer),
+            VoiceSegment::Lang(lang) => lang.write(writer),
+            VoiceSegment::Paragraph(paragraph) => paragraph.write(writer),
+            VoiceSegment::Sentence(sentence) => sentence.write(writer),
+            VoiceSegment::Audio(audio) => audio.write(writer),
+            VoiceSegment::Break(pause) => pause.write(writer),
+            VoiceSegment::Prosody(prosody) => prosody.write(writer),
+            VoiceSegment::SayAs(say_as) => say_as.write(writer),
+            VoiceSegment::Phoneme(phoneme) => phoneme.write(writer),
+            VoiceSegment::Bookmark(bookmark) => bookmark.write(writer),
+        }
     }
 }
 
-#[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "mstts:express-as")]
+#[derive(Debug, PartialEq)]
 pub struct ExpressAs {
-    #[serde(rename = "style")]
-    style: Style,
-    #[serde(rename = "$value")]
-    body: String,
+    pub(crate) expression: Expression,
+    pub(crate) body: Vec<VoiceSegment>,
+}
+
+impl ExpressAs {
+    fn write(&self, writer: &mut XmlWriter) {
+        let style_degree = self
+            .expression
+            .style_degree
+            .map(|style_degree| style_degree.to_string());
+        let mut attributes = vec![("style", self.expression.style.as_string())];
+        if let Some(style_degree) = &style_degree {
+            attributes.push(("styledegree", style_degree));
+        }
+        if let Some(role) = &self.expression.role {
+            attributes.push(("role", role.as_string()));
+        }
+        writer.start("mstts:express-as", &attributes);
+        for segment in &self.body {
+            segment.write(writer);
+        }
+        writer.end("mstts:express-as");
+    }
+}
+
+/// Speaking style with optional intensity and role-play
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    pub(crate) style: Style,
+    pub(crate) style_degree: Option<f32>,
+    pub(crate) role: Option<Role>,
+}
+
+impl Expression {
+    pub fn new(style: Style) -> Self {
+        Self {
+            style,
+            style_degree: None,
+            role: None,
+        }
+    }
+
+    /// Intensity of the style between 0.01 and 2. Default is 1
+    pub fn with_style_degree(mut self, style_degree: f32) -> Result<Self> {
+        if !(MIN_STYLE_DEGREE..=MAX_STYLE_DEGREE).contains(&style_degree) {
+            return Err(TtsError::InvalidValue(format!(
+                "style degree {} is outside of {} to {}",
+                style_degree, MIN_STYLE_DEGREE, MAX_STYLE_DEGREE
+            )));
+        }
+        self.style_degree = Some(style_degree);
+        Ok(self)
+    }
+
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.role = Some(role);
+        self
+    }
+}
+
+impl From<Style> for Expression {
+    fn from(style: Style) -> Self {
+        Expression::new(style)
+    }
 }
 
 // <mstts:silence type="Sentenceboundary" value="200ms"/>
-#[derive(Debug, Serialize, PartialEq)]
-#[serde(rename = "mstts:silence")]
+#[derive(Debug, PartialEq)]
 pub struct SilenceAttribute {
-    #[serde(rename = "type")]
-    attribute_type: SilenceAttributeType,
-    value: String,
+    pub(crate) attribute_type: SilenceAttributeType,
+    pub(crate) value: Duration,
+}
+
+impl SilenceAttribute {
+    fn write(&self, writer: &mut XmlWriter) {
+        writer.empty(
+            "mstts:silence",
+            &[
+                ("type", self.attribute_type.as_string()),
+                ("value", &format_time(self.value)),
+            ],
+        );
+    }
+}
+
+// <sub alias="World Wide Web Consortium">W3C</sub>
+#[derive(Debug, PartialEq)]
+pub struct Sub {
+    pub(crate) alias: String,
+    pub(crate) body: String,
+}
+
+impl Sub {
+    fn write(&self, writer: &mut XmlWriter) {
+        writer.start("sub", &[("alias", &self.alias)]);
+        writer.text(&self.body);
+        writer.end("sub");
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Emphasis {
+    pub(crate) level: EmphasisLevel,
+    pub(crate) body: Vec<VoiceSegment>,
+}
+
+impl Emphasis {
+    fn write(&self, writer: &mut XmlWriter) {
+        writer.start("emphasis", &[("level", self.level.as_string())]);
+        for segment in &self.body {
+            segment.write(writer);
+        }
+        writer.end("emphasis");
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Lang {
+    pub(crate) xml_lang: Locale,
+    pub(crate) body: Vec<VoiceSegment>,
+}
+
+impl Lang {
+    fn write(&self, writer: &mut XmlWriter) {
+        writer.start("lang", &[("xml:lang", self.xml_lang.as_str())]);
+        for segment in &self.body {
+            segment.write(writer);
+        }
+        writer.end("lang");
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Paragraph {
+    pub(crate) body: Vec<VoiceSegment>,
+}
+
+impl Paragraph {
+    fn write(&self, writer: &mut XmlWriter) {
+        writer.start("p", &[]);
+        for segment in &self.body {
+            segment.write(writer);
+        }
+        writer.end("p");
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Sentence {
+    pub(crate) body: Vec<VoiceSegment>,
+}
+
+impl Sentence {
+    fn write(&self, writer: &mut XmlWriter) {
+        writer.start("s", &[]);
+        for segment in &self.body {
+            segment.write(writer);
+        }
+        writer.end("s");
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Audio {
+    pub(crate) src: String,
+    pub(crate) body: Vec<VoiceSegment>,
+}
+
+impl Audio {
+    fn write(&self, writer: &mut XmlWriter) {
+        if self.body.is_empty() {
+            writer.empty("audio", &[("src", &self.src)]);
+        } else {
+            writer.start("audio", &[("src", &self.src)]);
+            for segment in &self.body {
+                segment.write(writer);
+            }
+            writer.end("audio");
+        }
+    }
+}
+
+// <break time="500ms"/>
+#[derive(Debug, PartialEq)]
+pub struct Break {
+    pub(crate) strength: Option<BreakStrength>,
+    pub(crate) time: Option<Duration>,
+}
+
+impl Break {
+    pub(crate) fn new(strength: Option<BreakStrength>, time: Option<Duration>) -> Result<Self> {
+        if let Some(time) = time.filter(|time| *time > MAX_BREAK) {
+            return Err(TtsError::InvalidValue(format!(
+                "break {}ms is longer than {}ms",
+                time.as_millis(),
+                MAX_BREAK.as_millis()
+            )));
+        }
+        Ok(Self { strength, time })
+    }
+
+    fn write(&self, writer: &mut XmlWriter) {
+        let time = self.time.map(format_time);
+        let mut attributes = vec![];
+        if let Some(strength) = &self.strength {
+            attributes.push(("strength", strength.as_string()));
+        }
+        if let Some(time) = &time {
+            attributes.push(("time", time.as_str()));
+        }
+        writer.empty("break", &attributes);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Prosody {
+    pub(crate) settings: ProsodySettings,
+    pub(crate) body: Vec<VoiceSegment>,
+}
+
+impl Prosody {
+    fn write(&self, writer: &mut XmlWriter) {
+        let mut attributes = vec![];
+        if let Some(rate) = &self.settings.rate {
+            attributes.push(("rate", rate.as_str()));
+        }
+        if let Some(pitch) = &self.settings.pitch {
+            attributes.push(("pitch", pitch.as_str()));
+        }
+        if let Some(volume) = &self.settings.volume {
+            attributes.push(("volume", volume.as_str()));
+        }
+        if let Some(contour) = &self.settings.contour {
+            attributes.push(("contour", contour.as_str()));
+        }
+        writer.start("prosody", &attributes);
+        for segment in &self.body {
+            segment.write(writer);
+        }
+        writer.end("prosody");
+    }
+}
+
+/// Prosody values in SSML notation such as `"+10%"`, `"x-slow"` or `"-2st"`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProsodySettings {
+    pub(crate) rate: Option<String>,
+    pub(crate) pitch: Option<String>,
+    pub(crate) volume: Option<String>,
+    pub(crate) contour: Option<String>,
+}
+
+impl ProsodySettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rate(mut self, rate: &str) -> Self {
+        self.rate = Some(rate.to_owned());
+        self
+    }
+
+    pub fn with_pitch(mut self, pitch: &str) -> Self {
+        self.pitch = Some(pitch.to_owned());
+        self
+    }
+
+    pub fn with_volume(mut self, volume: &str) -> Self {
+        self.volume = Some(volume.to_owned());
+        self
+    }
+
+    pub fn with_contour(mut self, contour: &str) -> Self {
+        self.contour = Some(contour.to_owned());
+        self
+    }
+}
+
+// <say-as interpret-as="date" format="mdy">10/19/2026</say-as>
+#[derive(Debug, PartialEq)]
+pub struct SayAs {
+    pub(crate) interpret_as: InterpretAs,
+    pub(crate) format: Option<String>,
+    pub(crate) detail: Option<String>,
+    pub(crate) body: String,
+}
+
+impl SayAs {
+    fn write(&self, writer: &mut XmlWriter) {
+        let mut attributes = vec![("interpret-as", self.interpret_as.as_string())];
+        if let Some(format) = &self.format {
+            attributes.push(("format", format.as_str()));
+        }
+        if let Some(detail) = &self.detail {
+            attributes.push(("detail", detail.as_str()));
+        }
+        writer.start("say-as", &attributes);
+        writer.text(&self.body);
+        writer.end("say-as");
+    }
+}
+
+// <phoneme alphabet="ipa" ph="təˈmeɪtoʊ">tomato</phoneme>
+#[derive(Debug, PartialEq)]
+pub struct Phoneme {
+    pub(crate) alphabet: PhoneticAlphabet,
+    pub(crate) ph: String,
+    pub(crate) body: String,
+}
+
+impl Phoneme {
+    fn write(&self, writer: &mut XmlWriter) {
+        writer.start(
+            "phoneme",
+            &[("alphabet", self.alphabet.as_string()), ("ph", &self.ph)],
+        );
+        writer.text(&self.body);
+        writer.end("phoneme");
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Bookmark {
+    pub(crate) mark: String,
+}
+
+impl Bookmark {
+    fn write(&self, writer: &mut XmlWriter) {
+        writer.empty("bookmark", &[("mark", &self.mark)]);
+    }
+}
+
+// <mstts:backgroundaudio src="https://example.com/jingle.wav" volume="0.7" fadein="3000" fadeout="4000"/>
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackgroundAudio {
+    pub(crate) src: String,
+    pub(crate) volume: Option<f32>,
+    pub(crate) fade_in: Option<Duration>,
+    pub(crate) fade_out: Option<Duration>,
+}
+
+impl BackgroundAudio {
+    pub fn new(src: &str) -> Result<Self> {
+        validate_audio_url(src)?;
+        Ok(Self {
+            src: src.to_owned(),
+            volume: None,
+            fade_in: None,
+            fade_out: None,
+        })
+    }
+
+    /// Volume between 0 and 1
+    pub fn with_volume(mut self, volume: f32) -> Result<Self> {
+        if !(0.0..=1.0).contains(&volume) {
+            return Err(TtsError::InvalidValue(format!(
+                "background audio volume {} is outside of 0 to 1",
+                volume
+            )));
+        }
+        self.volume = Some(volume);
+        Ok(self)
+    }
+
+    /// Fade in of up to 10 seconds
+    pub fn with_fade_in(mut self, fade_in: Duration) -> Result<Self> {
+        validate_fade(fade_in)?;
+        self.fade_in = Some(fade_in);
+        Ok(self)
+    }
+
+    /// Fade out of up to 10 seconds
+    pub fn with_fade_out(mut self, fade_out: Duration) -> Result<Self> {
+        validate_fade(fade_out)?;
+        self.fade_out = Some(fade_out);
+        Ok(self)
+    }
+
+    fn write(&self, writer: &mut XmlWriter) {
+        let volume = self.volume.map(|volume| volume.to_string());
+        let fade_in = self.fade_in.map(|fade| fade.as_millis().to_string());
+        let fade_out = self.fade_out.map(|fade| fade.as_millis().to_string());
+        let mut attributes = vec![("src", self.src.as_str())];
+        if let Some(volume) = &volume {
+            attributes.push(("volume", volume));
+        }
+        if let Some(fade_in) = &fade_in {
+            attributes.push(("fadein", fade_in));
+        }
+        if let Some(fade_out) = &fade_out {
+            attributes.push(("fadeout", fade_out));
+        }
+        writer.empty("mstts:backgroundaudio", &attributes);
+    }
+}
+
+fn validate_fade(fade: Duration) -> Result<()> {
+    if fade > MAX_BACKGROUND_AUDIO_FADE {
+        return Err(TtsError::InvalidValue(format!(
+            "background audio fade {}ms is longer than {}ms",
+            fade.as_millis(),
+            MAX_BACKGROUND_AUDIO_FADE.as_millis()
+        )));
+    }
+    Ok(())
+}
+
+/// Time value in milliseconds, keeping fractions so that parsing it gives back the same duration
+fn format_time(time: Duration) -> String {
+    let nanos = time.subsec_nanos() % 1_000_000;
+    if nanos == 0 {
+        return format!("{}ms", time.as_millis());
+    }
+    let fraction = format!("{:06}", nanos);
+    format!("{}.{}ms", time.as_millis(), fraction.trim_end_matches('0'))
+}
+
+/// The service only fetches audio over https
+pub(crate) fn validate_audio_url(url: &str) -> Result<()> {
+    let host = url
+        .strip_prefix("https://")
+        .map(|rest| rest.split(&['/', '?', '#'][..]).next().unwrap_or_default())
+        .unwrap_or_default();
+    if host.is_empty() || url.chars().any(char::is_whitespace) {
+        return Err(TtsError::InvalidUrl(url.to_owned()));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::EnUsVoices;
 
     #[test]
     fn xml_serialization_plain_text() {
         let speak = Speak::with_text(
-            "en-US",
+            &Locale::en_us(),
             VoiceGender::Female,
             "en-US-JennyNeural",
             "lorem ipsum",
@@ -175,10 +839,21 @@ lorem ipsum\
         assert_eq!(expected, &ssml);
     }
 
+    #[test]
+    fn xml_serialization_other_gender() {
+        let voice_settings = VoiceSettings::new(
+            "en-US-SkyNeural",
+            Locale::en_us(),
+            VoiceGender::Other("Nonbinary".to_owned()),
+        );
+        let ssml = Speak::text_with_voice_settings(&voice_settings, "lorem ipsum").to_ssml_xml();
+        assert!(ssml.contains("<voice xml:lang=\"en-US\" name=\"en-US-SkyNeural\">"));
+    }
+
     #[test]
     fn xml_serialization_express_as_single() {
         let speak = Speak::with_segments(
-            "en-US",
+            &Locale::en_us(),
             VoiceGender::Female,
             "en-US-SaraNeural",
             vec![VoiceSegment::with_expression(
@@ -200,11 +875,15 @@ xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
     #[test]
     fn xml_serialization_silence_attribute() {
         let speak = Speak::with_segments(
-            "en-US",
+            &Locale::en_us(),
             VoiceGender::Female,
             "en-US-SaraNeural",
             vec![
-                VoiceSegment::silence(SilenceAttributeType::Sentenceboundary, "100ms".to_owned()),
+                VoiceSegment::silence(
+                    SilenceAttributeType::Sentenceboundary,
+                    Duration::from_millis(100),
+                )
+                .unwrap(),
                 VoiceSegment::plain("lorem ipsum"),
             ],
         );
@@ -222,7 +901,7 @@ xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
     #[test]
     fn xml_serialization_express_as_multiple() {
         let speak = Speak::with_segments(
-            "en-US",
+            &Locale::en_us(),
             VoiceGender::Female,
             "en-US-SaraNeural",
             vec![
@@ -245,7 +924,7 @@ xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
     #[test]
     fn xml_serialization_plain_and_express_as() {
         let speak = Speak::with_segments(
-            "en-US",
+            &Locale::en_us(),
             VoiceGender::Female,
             "en-US-SaraNeural",
             vec![
@@ -268,11 +947,382 @@ lorem ipsum\
     #[test]
     fn express_as_serialization() {
         let express = ExpressAs {
-            style: Style::Angry,
-            body: String::from("lorem"),
+            expression: Expression::new(Style::Angry),
+            body: vec![VoiceSegment::plain("lorem")],
         };
-        let xml = quick_xml::se::to_string(&express).unwrap();
+        let mut writer = XmlWriter::new();
+        express.write(&mut writer);
+        let xml = writer.into_string();
         let expected = "<mstts:express-as style=\"angry\">lorem</mstts:express-as>";
         assert_eq!(expected, &xml);
     }
+
+    #[test]
+    fn xml_serialization_lexicon() {
+        let speak = Speak::with_text(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyNeural",
+            "BTW, we will be there",
+        )
+        .with_lexicon("https://example.com/lexicon.xml");
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+<lexicon uri=\"https://example.com/lexicon.xml\"/>\
+BTW, we will be there\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn xml_serialization_sub() {
+        let speak = Speak::with_segments(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyNeural",
+            vec![
+                VoiceSegment::sub("W3C", "World Wide Web Consortium"),
+                VoiceSegment::plain(" publishes SSML"),
+            ],
+        );
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+<sub alias=\"World Wide Web Consortium\">W3C</sub> publishes SSML\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn xml_serialization_nested_emphasis_and_lang() {
+        let speak = Speak::with_segments(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyMultilingualNeural",
+            vec![
+                VoiceSegment::plain("Say "),
+                VoiceSegment::lang(
+                    &"de-DE".parse().unwrap(),
+                    vec![
+                        VoiceSegment::plain("ich bin "),
+                        VoiceSegment::emphasis(
+                            EmphasisLevel::Strong,
+                            vec![VoiceSegment::plain("sehr")],
+                        ),
+                        VoiceSegment::plain(" müde"),
+                    ],
+                ),
+            ],
+        );
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyMultilingualNeural\">\
+Say <lang xml:lang=\"de-DE\">ich bin <emphasis level=\"strong\">sehr</emphasis> müde</lang>\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn paragraphs_from_text() {
+        let text = "First sentence. Second one!\nStill second paragraph line?\n\n\n  Another paragraph  \n";
+        let segments = VoiceSegment::paragraphs(text);
+        let expected = vec![
+            VoiceSegment::paragraph(vec![
+                VoiceSegment::sentence(vec![VoiceSegment::plain("First sentence.")]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("Second one!")]),
+                VoiceSegment::sentence(vec![VoiceSegment::plain("Still second paragraph line?")]),
+            ]),
+            VoiceSegment::paragraph(vec![VoiceSegment::sentence(vec![VoiceSegment::plain(
+                "Another paragraph",
+            )])]),
+        ];
+        assert_eq!(expected, segments);
+    }
+
+    #[test]
+    fn xml_serialization_paragraph() {
+        let speak = Speak::with_segments(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyNeural",
+            VoiceSegment::paragraphs("Hello there. How are you?\n\nBye."),
+        );
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+<p><s>Hello there.</s><s>How are you?</s></p>\
+<p><s>Bye.</s></p>\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn xml_serialization_audio() {
+        let speak = Speak::with_segments(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyNeural",
+            vec![
+                VoiceSegment::audio(
+                    "https://example.com/jingle.wav",
+                    vec![VoiceSegment::plain("Welcome")],
+                )
+                .unwrap(),
+                VoiceSegment::plain(" to the show"),
+            ],
+        );
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+<audio src=\"https://example.com/jingle.wav\">Welcome</audio> to the show\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn xml_serialization_background_audio() {
+        let background_audio = BackgroundAudio::new("https://example.com/music.wav")
+            .unwrap()
+            .with_volume(0.7)
+            .unwrap()
+            .with_fade_in(Duration::from_millis(3000))
+            .unwrap()
+            .with_fade_out(Duration::from_secs(4))
+            .unwrap();
+        let speak = Speak::with_text(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyNeural",
+            "lorem ipsum",
+        )
+        .with_background_audio(background_audio);
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<mstts:backgroundaudio src=\"https://example.com/music.wav\" volume=\"0.7\" fadein=\"3000\" fadeout=\"4000\"/>\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+lorem ipsum\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn audio_url_validation() {
+        assert!(VoiceSegment::audio("https://example.com/a.wav", vec![]).is_ok());
+        assert!(VoiceSegment::audio("http://example.com/a.wav", vec![]).is_err());
+        assert!(VoiceSegment::audio("https:///a.wav", vec![]).is_err());
+        assert!(VoiceSegment::audio("https://example.com/a b.wav", vec![]).is_err());
+        assert!(BackgroundAudio::new("file:///tmp/a.wav").is_err());
+    }
+
+    #[test]
+    fn background_audio_range_validation() {
+        let background_audio = BackgroundAudio::new("https://example.com/music.wav").unwrap();
+        assert!(background_audio.clone().with_volume(1.5).is_err());
+        assert!(background_audio
+            .clone()
+            .with_fade_in(Duration::from_secs(11))
+            .is_err());
+        assert!(background_audio
+            .with_fade_out(Duration::from_secs(10))
+            .is_ok());
+    }
+
+    #[test]
+    fn xml_serialization_multiple_voices() {
+        let speak = Speak::voices_with_voice_settings(vec![
+            (
+                EnUsVoices::JennyNeural.to_voice_settings(),
+                vec![VoiceSegment::plain("How are you?")],
+            ),
+            (
+                EnUsVoices::GuyNeural.to_voice_settings(),
+                vec![VoiceSegment::with_expression("Great!", Style::Cheerful)],
+            ),
+        ])
+        .with_lexicon("https://example.com/lexicon.xml");
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+<lexicon uri=\"https://example.com/lexicon.xml\"/>How are you?\
+</voice>\
+<voice xml:lang=\"en-US\" xml:gender=\"Male\" name=\"en-US-GuyNeural\">\
+<lexicon uri=\"https://example.com/lexicon.xml\"/>\
+<mstts:express-as style=\"cheerful\">Great!</mstts:express-as>\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn xml_serialization_express_as_degree_and_role() {
+        let expression = Expression::new(Style::Sad)
+            .with_style_degree(1.5)
+            .unwrap()
+            .with_role(Role::YoungAdultFemale);
+        let speak = Speak::with_segments(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-SaraNeural",
+            vec![VoiceSegment::express_as(
+                expression,
+                vec![
+                    VoiceSegment::plain("lorem "),
+                    VoiceSegment::emphasis(
+                        EmphasisLevel::Moderate,
+                        vec![VoiceSegment::plain("ipsum")],
+                    ),
+                ],
+            )],
+        );
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-SaraNeural\">\
+<mstts:express-as style=\"sad\" styledegree=\"1.5\" role=\"YoungAdultFemale\">\
+lorem <emphasis level=\"moderate\">ipsum</emphasis>\
+</mstts:express-as>\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn style_degree_validation() {
+        let expression = Expression::new(Style::Cheerful);
+        assert!(expression.clone().with_style_degree(0.01).is_ok());
+        assert!(expression.clone().with_style_degree(2.0).is_ok());
+        assert!(expression.clone().with_style_degree(0.0).is_err());
+        assert!(expression.with_style_degree(2.5).is_err());
+    }
+
+    #[test]
+    fn xml_serialization_exact_silence() {
+        let speak = Speak::with_segments(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-SaraNeural",
+            vec![
+                VoiceSegment::silence(SilenceAttributeType::CommaExact, Duration::from_secs(1))
+                    .unwrap(),
+                VoiceSegment::plain("one, two"),
+            ],
+        );
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-SaraNeural\">\
+<mstts:silence type=\"Comma-exact\" value=\"1000ms\"/>one, two\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn silence_range_validation() {
+        assert!(
+            VoiceSegment::silence(SilenceAttributeType::Leading, Duration::from_millis(5000))
+                .is_ok()
+        );
+        assert!(
+            VoiceSegment::silence(SilenceAttributeType::Leading, Duration::from_millis(5001))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn xml_serialization_break_prosody_bookmark() {
+        let speak = Speak::with_segments(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyNeural",
+            vec![
+                VoiceSegment::bookmark("start"),
+                VoiceSegment::prosody(
+                    ProsodySettings::new().with_rate("+10%").with_pitch("-2st"),
+                    vec![VoiceSegment::plain("Today")],
+                ),
+                VoiceSegment::pause(Duration::from_millis(500)).unwrap(),
+                VoiceSegment::pause_with_strength(BreakStrength::XStrong),
+            ],
+        );
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+<bookmark mark=\"start\"/>\
+<prosody rate=\"+10%\" pitch=\"-2st\">Today</prosody>\
+<break time=\"500ms\"/><break strength=\"x-strong\"/>\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn xml_serialization_say_as() {
+        let speak = Speak::with_segments(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyNeural",
+            vec![
+                VoiceSegment::plain("Today is "),
+                VoiceSegment::say_as("10/19", InterpretAs::Date, Some("md")),
+            ],
+        );
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+Today is <say-as interpret-as=\"date\" format=\"md\">10/19</say-as>\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
+
+    #[test]
+    fn xml_serialization_phoneme() {
+        let speak = Speak::with_segments(
+            &Locale::en_us(),
+            VoiceGender::Female,
+            "en-US-JennyNeural",
+            vec![VoiceSegment::phoneme(
+                "tomato",
+                PhoneticAlphabet::Ipa,
+                "təˈmeɪtoʊ",
+            )],
+        );
+
+        let ssml = speak.to_ssml_xml();
+        let expected = "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+xmlns:mstts=\"https://www.w3.org/2001/mstts\" xml:lang=\"en-US\">\
+<voice xml:lang=\"en-US\" xml:gender=\"Female\" name=\"en-US-JennyNeural\">\
+<phoneme alphabet=\"ipa\" ph=\"təˈmeɪtoʊ\">tomato</phoneme>\
+</voice>\
+</speak>";
+        assert_eq!(expected, &ssml);
+    }
 }
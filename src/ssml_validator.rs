@@ -0,0 +1,711 @@
+/// Checks ssml against the limits of the service before it's sent.
+///
+/// All problems are collected instead of stopping at the first one. Elements, attributes and
+/// values this crate doesn't know are warnings since the service may support them.
+use crate::{
+    error::{Severity, ValidationProblem},
+    locale,
+    ssml_parser::parse_time,
+    ssml_serializer::{
+        validate_audio_url, MAX_BACKGROUND_AUDIO_FADE, MAX_BREAK, MAX_SILENCE, MAX_STYLE_DEGREE,
+        MIN_STYLE_DEGREE,
+    },
+    xml::line_column,
+    BreakStrength, EmphasisLevel, PhoneticAlphabet, Result, Role, SilenceAttributeType, Speak,
+    TtsError,
+};
+use quick_xml::{events::Event, Reader};
+use std::str::FromStr;
+
+/// Maximum number of voice and audio elements in a single request
+pub const MAX_VOICE_ELEMENTS: usize = 50;
+/// Maximum size of a single ssml request in bytes
+pub const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+/// How an element may be used
+struct ElementRule {
+    name: &'static str,
+    attributes: &'static [&'static str],
+    required: &'static [&'static str],
+    content: Content,
+}
+
+#[derive(PartialEq)]
+enum Content {
+    Empty,
+    Text,
+    Mixed,
+}
+
+const ELEMENT_RULES: &[ElementRule] = &[
+    ElementRule {
+        name: "speak",
+        attributes: &["version", "xml:lang"],
+        required: &["xml:lang"],
+        content: Content::Mixed,
+    },
+    ElementRule {
+        name: "voice",
+        attributes: &["name", "xml:lang", "xml:gender", "effect"],
+        required: &["name"],
+        content: Content::Mixed,
+    },
+    ElementRule {
+        name: "lexicon",
+        attributes: &["uri"],
+        required: &["uri"],
+        content: Content::Empty,
+    },
+    ElementRule {
+        name: "mstts:backgroundaudio",
+        attributes: &["src", "volume", "fadein", "fadeout"],
+        required: &["src"],
+        content: Content::Empty,
+    },
+    ElementRule {
+        name: "mstts:express-as",
+        attributes: &["style", "styledegree", "role"],
+        required: &["style"],
+        content: Content::Mixed,
+    },
+    ElementRule {
+        name: "mstts:silence",
+        attributes: &["type", "value"],
+        required: &["type", "value"],
+        content: Content::Empty,
+    },
+    ElementRule {
+        name: "mstts:viseme",
+        attributes: &["type"],
+        required: &["type"],
+        content: Content::Empty,
+    },
+    ElementRule {
+        name: "mstts:audioduration",
+        attributes: &["value"],
+        required: &["value"],
+        content: Content::Empty,
+    },
+    ElementRule {
+        name: "sub",
+        attributes: &["alias"],
+        required: &["alias"],
+        content: Content::Text,
+    },
+    ElementRule {
+        name: "emphasis",
+        attributes: &["level"],
+        required: &[],
+        content: Content::Mixed,
+    },
+    ElementRule {
+        name: "lang",
+        attributes: &["xml:lang"],
+        required: &["xml:lang"],
+        content: Content::Mixed,
+    },
+    ElementRule {
+        name: "p",
+        attributes: &[],
+        required: &[],
+        content: Content::Mixed,
+    },
+    ElementRule {
+        name: "s",
+        attributes: &[],
+        required: &[],
+        content: Content::Mixed,
+    },
+    ElementRule {
+        name: "audio",
+        attributes: &["src"],
+        required: &["src"],
+        content: Content::Mixed,
+    },
+    ElementRule {
+        name: "break",
+        attributes: &["strength", "time"],
+        required: &[],
+        content: Content::Empty,
+    },
+    ElementRule {
+        name: "prosody",
+        attributes: &["rate", "pitch", "volume", "contour", "range"],
+        required: &[],
+        content: Content::Mixed,
+    },
+    ElementRule {
+        name: "say-as",
+        attributes: &["interpret-as", "format", "detail"],
+        required: &["interpret-as"],
+        content: Content::Text,
+    },
+    ElementRule {
+        name: "phoneme",
+        attributes: &["alphabet", "ph"],
+        required: &["alphabet", "ph"],
+        content: Content::Text,
+    },
+    ElementRule {
+        name: "bookmark",
+        attributes: &["mark"],
+        required: &["mark"],
+        content: Content::Empty,
+    },
+];
+
+/// Elements allowed directly inside of speak. Everything else has to be inside of a voice
+const SPEAK_CHILDREN: &[&str] = &["voice", "lexicon", "mstts:backgroundaudio"];
+
+impl Speak {
+    /// Validate the document as it would be sent to the service
+    pub fn validate(&self) -> Result<()> {
+        validate_ssml(&self.to_ssml_xml())
+    }
+}
+
+/// Validate raw ssml. Returns [`TtsError::InvalidSsml`] listing every problem found if any of
+/// them is an error, warnings alone pass
+pub fn validate_ssml(ssml: &str) -> Result<()> {
+    let problems = check_ssml(ssml);
+    if problems
+        .iter()
+        .any(|problem| problem.severity == Severity::Error)
+    {
+        Err(TtsError::InvalidSsml(problems))
+    } else {
+        Ok(())
+    }
+}
+
+/// Every problem of raw ssml including warnings
+pub fn check_ssml(ssml: &str) -> Vec<ValidationProblem> {
+    SsmlValidator::new(ssml).validate()
+}
+
+struct SsmlValidator<'a> {
+    source: &'a str,
+    problems: Vec<ValidationProblem>,
+    voice_elements: usize,
+    voice_seen: bool,
+}
+
+impl<'a> SsmlValidator<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            problems: vec![],
+            voice_elements: 0,
+            voice_seen: false,
+        }
+    }
+
+    fn validate(mut self) -> Vec<ValidationProblem> {
+        if self.source.len() > MAX_REQUEST_SIZE {
+            self.problem(
+                0,
+                format!(
+                    "document is {} bytes, the maximum is {} bytes",
+                    self.source.len(),
+                    MAX_REQUEST_SIZE
+                ),
+            );
+        }
+        let mut reader = Reader::from_str(self.source);
+        let mut buf = Vec::new();
+        let mut stack: Vec<String> = vec![];
+        let mut root_seen = false;
+        loop {
+            let position = reader.buffer_position();
+            buf.clear();
+            let event = reader.read_event(&mut buf);
+            match event {
+                Ok(Event::Start(ref element)) | Ok(Event::Empty(ref element)) => {
+                    let name = String::from_utf8_lossy(element.name()).into_owned();
+                    let mut attributes = vec![];
+                    for attribute in element.attributes() {
+                        match attribute {
+                            Ok(attribute) => {
+                                let key = String::from_utf8_lossy(attribute.key).into_owned();
+                                match attribute.unescape_and_decode_value(&reader) {
+                                    Ok(value) => attributes.push((key, value)),
+                                    Err(error) => self.problem(position, error),
+                                }
+                            }
+                            Err(error) => self.problem(position, error),
+                        }
+                    }
+                    if stack.is_empty() {
+                        if root_seen {
+                            self.problem(position, "multiple root elements");
+                        }
+                        root_seen = true;
+                        if name != "speak" {
+                            self.problem(
+                                position,
+                                format!("root element <{}> must be <speak>", name),
+                            );
+                        }
+                    }
+                    self.check_element(position, &name, stack.last(), &attributes);
+                    if matches!(event, Ok(Event::Start(_))) {
+                        stack.push(name);
+                    }
+                }
+                Ok(Event::Text(ref text)) => {
+                    let has_text = match text.unescape_and_decode(&reader) {
+                        Ok(text) => !text.trim().is_empty(),
+                        Err(error) => {
+                            self.problem(position, error);
+                            false
+                        }
+                    };
+                    if has_text {
+                        self.check_text(position, stack.last());
+                    }
+                }
+                Ok(Event::CData(ref text)) => {
+                    if !String::from_utf8_lossy(text).trim().is_empty() {
+                        self.check_text(position, stack.last());
+                    }
+                }
+                Ok(Event::End(_)) => {
+                    stack.pop();
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => (),
+                Err(error) => {
+                    // the rest of a malformed document can't be checked
+                    self.problem(reader.buffer_position(), error);
+                    return self.problems;
+                }
+            }
+        }
+        if let Some(name) = stack.last() {
+            self.problem(self.source.len(), format!("unclosed element <{}>", name));
+        }
+        if !root_seen {
+            self.problem(self.source.len(), "missing <speak> element");
+        } else if !self.voice_seen {
+            self.problem(0, "<speak> needs at least one <voice>");
+        }
+        if self.voice_elements > MAX_VOICE_ELEMENTS {
+            self.problem(
+                0,
+                format!(
+                    "document has {} voice and audio elements, the maximum is {}",
+                    self.voice_elements, MAX_VOICE_ELEMENTS
+                ),
+            );
+        }
+        self.problems
+    }
+
+    fn check_element(
+        &mut self,
+        position: usize,
+        name: &str,
+        parent: Option<&String>,
+        attributes: &[(String, String)],
+    ) {
+        let rule = match ELEMENT_RULES.iter().find(|rule| rule.name == name) {
+            Some(rule) => rule,
+            None => {
+                self.warning(position, format!("unknown element <{}>", name));
+                return;
+            }
+        };
+        if let Some(parent) = parent {
+            let parent_rule = ELEMENT_RULES.iter().find(|rule| rule.name == parent);
+            match parent_rule.map(|rule| &rule.content) {
+                Some(Content::Empty) => self.problem(
+                    position,
+                    format!(
+                        "<{}> is not allowed inside of empty element <{}>",
+                        name, parent
+                    ),
+                ),
+                Some(Content::Text) => self.problem(
+                    position,
+                    format!(
+                        "<{}> is not allowed inside of <{}>, only text is",
+                        name, parent
+                    ),
+                ),
+                _ => (),
+            }
+            let in_speak = parent == "speak";
+            let speak_child = SPEAK_CHILDREN.contains(&name);
+            if in_speak && !speak_child {
+                self.problem(position, format!("<{}> must be inside of a <voice>", name));
+            }
+            if !in_speak && (name == "voice" || name == "mstts:backgroundaudio") {
+                self.problem(position, format!("<{}> must be a child of <speak>", name));
+            }
+        }
+        if name == "voice" || name == "audio" {
+            self.voice_elements += 1;
+        }
+        self.voice_seen |= name == "voice";
+        for (key, _) in attributes {
+            let is_namespace = key == "xmlns" || key.starts_with("xmlns:");
+            if !(rule.attributes.contains(&key.as_str()) || name == "speak" && is_namespace) {
+                self.warning(position, format!("unknown attribute {} on <{}>", key, name));
+            }
+        }
+        for required in rule.required {
+            if !attributes.iter().any(|(key, _)| key == required) {
+                self.problem(
+                    position,
+                    format!("missing {} attribute on <{}>", required, name),
+                );
+            }
+        }
+        for (key, value) in attributes {
+            if let Some(message) = check_value(name, key, value) {
+                self.problem(position, format!("{} on <{}>: {}", key, name, message));
+            }
+            if let Some(message) = check_known_value(name, key, value) {
+                self.warning(position, format!("{} on <{}>: {}", key, name, message));
+            }
+        }
+    }
+
+    fn check_text(&mut self, position: usize, parent: Option<&String>) {
+        match parent.map(String::as_str) {
+            None | Some("speak") => self.problem(position, "text must be inside of a <voice>"),
+            Some(parent) => {
+                let empty = ELEMENT_RULES
+                    .iter()
+                    .any(|rule| rule.name == parent && rule.content == Content::Empty);
+                if empty {
+                    self.problem(position, format!("<{}> must be empty", parent));
+                }
+            }
+        }
+    }
+
+    fn problem(&mut self, position: usize, message: impl ToString) {
+        self.push(position, message, Severity::Error);
+    }
+
+    fn warning(&mut self, position: usize, message: impl ToString) {
+        self.push(position, message, Severity::Warning);
+    }
+
+    fn push(&mut self, position: usize, message: impl ToString, severity: Severity) {
+        let (line, column) = line_column(self.source, position);
+        self.problems.push(ValidationProblem {
+            line,
+            column,
+            message: message.to_string(),
+            severity,
+        });
+    }
+}
+
+/// Check attribute value ranges. Returns a description of the problem
+fn check_value(element: &str, key: &str, value: &str) -> Option<String> {
+    let value = value.trim();
+    match (element, key) {
+        ("mstts:express-as", "styledegree") => {
+            check_range(value, MIN_STYLE_DEGREE, MAX_STYLE_DEGREE)
+        }
+        ("mstts:silence", "value") => check_time(value, MAX_SILENCE.as_millis()),
+        ("break", "time") => check_time(value, MAX_BREAK.as_millis()),
+        ("break", "strength") => check_parse::<BreakStrength>(value),
+        ("emphasis", "level") => check_parse::<EmphasisLevel>(value),
+        ("phoneme", "alphabet") => check_parse::<PhoneticAlphabet>(value),
+        ("audio", "src") | ("mstts:backgroundaudio", "src") => validate_audio_url(value)
+            .err()
+            .map(|_| format!("{:?} is not an https url", value)),
+        ("mstts:backgroundaudio", "volume") => check_range(value, 0.0, 1.0),
+        ("mstts:backgroundaudio", "fadein") | ("mstts:backgroundaudio", "fadeout") => {
+            check_range(value, 0.0, MAX_BACKGROUND_AUDIO_FADE.as_millis() as f32)
+        }
+        ("prosody", "rate") => check_prosody(
+            value,
+            &["x-slow", "slow", "medium", "fast", "x-fast", "default"],
+            Some((0.5, 2.0)),
+            (-50.0, 100.0),
+            &[],
+        ),
+        ("prosody", "pitch") => check_prosody(
+            value,
+            &["x-low", "low", "medium", "high", "x-high", "default"],
+            None,
+            (-50.0, 50.0),
+            &["Hz", "st"],
+        ),
+        ("prosody", "volume") => check_prosody(
+            value,
+            &[
+                "silent", "x-soft", "soft", "medium", "loud", "x-loud", "default",
+            ],
+            Some((0.0, 100.0)),
+            (-100.0, 100.0),
+            &[],
+        ),
+        (_, "xml:lang") => locale::validate(value)
+            .err()
+            .map(|error| format!("invalid language {:?}: {}", value, error)),
+        _ => None,
+    }
+}
+
+/// Check values of open ended attributes against the values this crate knows
+fn check_known_value(element: &str, key: &str, value: &str) -> Option<String> {
+    let value = value.trim();
+    match (element, key) {
+        ("mstts:express-as", "role") => check_parse::<Role>(value),
+        ("mstts:silence", "type") => matches!(
+            value.parse::<SilenceAttributeType>(),
+            Ok(SilenceAttributeType::Other(_))
+        )
+        .then(|| format!("unknown silence type {:?}", value)),
+        _ => None,
+    }
+}
+
+/// Why `value` isn't one of the values of `T`
+fn check_parse<T: FromStr<Err = TtsError>>(value: &str) -> Option<String> {
+    match value.parse::<T>() {
+        Ok(_) => None,
+        Err(TtsError::InvalidValue(message)) => Some(message),
+        Err(error) => Some(error.to_string()),
+    }
+}
+
+fn check_range(value: &str, min: f32, max: f32) -> Option<String> {
+    match value.parse::<f32>() {
+        Ok(number) if (min..=max).contains(&number) => None,
+        Ok(number) => Some(format!("{} is outside of {} to {}", number, min, max)),
+        Err(_) => Some(format!("{:?} is not a number", value)),
+    }
+}
+
+fn check_time(value: &str, max_milliseconds: u128) -> Option<String> {
+    match parse_time(value) {
+        Some(time) if time.as_millis() <= max_milliseconds => None,
+        Some(time) => Some(format!(
+            "{}ms is longer than {}ms",
+            time.as_millis(),
+            max_milliseconds
+        )),
+        None => Some(format!("{:?} is not a time such as 500ms or 2s", value)),
+    }
+}
+
+/// Prosody values are either named, an absolute number, a relative percentage or a relative number with a unit
+fn check_prosody(
+    value: &str,
+    named: &[&str],
+    absolute: Option<(f32, f32)>,
+    percent: (f32, f32),
+    units: &[&str],
+) -> Option<String> {
+    if named.contains(&value) {
+        return None;
+    }
+    if let Some(number) = value.strip_suffix('%') {
+        return check_range(number, percent.0, percent.1);
+    }
+    for unit in units {
+        if let Some(number) = value.strip_suffix(unit) {
+            return number
+                .parse::<f32>()
+                .is_err()
+                .then(|| format!("{:?} is not a number", number));
+        }
+    }
+    let relative = value.starts_with('+') || value.starts_with('-');
+    match absolute {
+        Some((min, max)) if !relative => check_range(value, min, max),
+        _ if relative && absolute.is_some() => value
+            .parse::<f32>()
+            .is_err()
+            .then(|| format!("{:?} is not a number", value)),
+        _ => Some(format!("unsupported value {:?}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnUsVoices, Expression, ProsodySettings, Style, VoiceSegment};
+    use std::time::Duration;
+
+    fn problems(ssml: &str) -> Vec<ValidationProblem> {
+        match validate_ssml(ssml) {
+            Ok(()) => vec![],
+            Err(TtsError::InvalidSsml(problems)) => problems,
+            Err(error) => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn valid_document() {
+        let speak = Speak::segments_with_voice_settings(
+            &EnUsVoices::JennyNeural.to_voice_settings(),
+            vec![
+                VoiceSegment::with_expression("hello", Style::Cheerful),
+                VoiceSegment::pause(Duration::from_millis(500)).unwrap(),
+                VoiceSegment::prosody(
+                    ProsodySettings::new()
+                        .with_rate("+20%")
+                        .with_pitch("-2st")
+                        .with_volume("loud"),
+                    vec![VoiceSegment::plain("world")],
+                ),
+            ],
+        );
+        assert!(speak.validate().is_ok());
+    }
+
+    #[test]
+    fn reports_all_problems_with_locations() {
+        let ssml = "<speak version=\"1.0\" xml:lang=\"en-US\">\n\
+<voice name=\"en-US-JennyNeural\" foo=\"bar\">\n\
+<break time=\"7s\"/><prosody rate=\"300%\">fast</prosody>\n\
+<mstts:express-as style=\"sad\" styledegree=\"3\">sad</mstts:express-as><blink/>\n\
+</voice>\n\
+</speak>";
+        let problems = problems(ssml);
+        let described: Vec<(usize, usize, &str)> = problems
+            .iter()
+            .map(|problem| (problem.line, problem.column, problem.message.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (2, 1, "unknown attribute foo on <voice>"),
+                (3, 1, "time on <break>: 7000ms is longer than 5000ms"),
+                (3, 19, "rate on <prosody>: 300 is outside of -50 to 100"),
+                (
+                    4,
+                    1,
+                    "styledegree on <mstts:express-as>: 3 is outside of 0.01 to 2"
+                ),
+                (4, 69, "unknown element <blink>"),
+            ],
+            described
+        );
+    }
+
+    #[test]
+    fn unknown_names_are_warnings() {
+        let ssml = "<speak xml:lang=\"en-US\"><voice name=\"a\">\
+<mstts:ttsembedding speakerProfileId=\"id\">hi</mstts:ttsembedding>\
+<mstts:silence type=\"Future-exact\" value=\"50ms\"/></voice></speak>";
+        assert!(validate_ssml(ssml).is_ok());
+        let warnings: Vec<String> = check_ssml(ssml)
+            .into_iter()
+            .filter(|problem| problem.severity == Severity::Warning)
+            .map(|problem| problem.message)
+            .collect();
+        assert_eq!(
+            vec![
+                "unknown element <mstts:ttsembedding>",
+                "type on <mstts:silence>: unknown silence type \"Future-exact\"",
+            ],
+            warnings
+        );
+
+        let speak = Speak::segments_with_voice_settings(
+            &EnUsVoices::JennyNeural.to_voice_settings(),
+            vec![VoiceSegment::silence(
+                SilenceAttributeType::Other("Future-exact".to_owned()),
+                Duration::from_millis(50),
+            )
+            .unwrap()],
+        );
+        assert!(speak.validate().is_ok());
+    }
+
+    #[test]
+    fn structure_problems() {
+        let ssml = "<speak xml:lang=\"en-US\">hello<break/><voice name=\"a\"><voice name=\"b\"/></voice></speak>";
+        let messages: Vec<String> = problems(ssml)
+            .into_iter()
+            .map(|problem| problem.message)
+            .collect();
+        assert_eq!(
+            vec![
+                "text must be inside of a <voice>",
+                "<break> must be inside of a <voice>",
+                "<voice> must be a child of <speak>",
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn invalid_language() {
+        let problems = problems("<speak xml:lang=\"Jenny\"><voice name=\"a\">text</voice></speak>");
+        assert_eq!(1, problems.len());
+        assert_eq!(
+            "xml:lang on <speak>: invalid language \"Jenny\": language must be 2 or 3 letters",
+            problems[0].message
+        );
+    }
+
+    #[test]
+    fn malformed_document() {
+        let problems = problems("<speak xml:lang=\"en-US\"><voice name=\"a\">text</speak>");
+        assert_eq!(1, problems.len());
+        assert_eq!(1, problems[0].line);
+    }
+
+    #[test]
+    fn voice_element_limit() {
+        let voices: Vec<_> = (0..=MAX_VOICE_ELEMENTS)
+            .map(|_| {
+                (
+                    EnUsVoices::GuyNeural.to_voice_settings(),
+                    vec![VoiceSegment::plain("hi")],
+                )
+            })
+            .collect();
+        let speak = Speak::voices_with_voice_settings(voices);
+        match speak.validate() {
+            Err(TtsError::InvalidSsml(problems)) => {
+                assert_eq!(1, problems.len());
+                assert!(problems[0].message.contains("51 voice and audio elements"));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn speak_without_voices() {
+        match Speak::voices_with_voice_settings(vec![]).validate() {
+            Err(TtsError::InvalidSsml(problems)) => {
+                assert_eq!(1, problems.len());
+                assert_eq!("<speak> needs at least one <voice>", problems[0].message);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn request_size_limit() {
+        let text = "a".repeat(MAX_REQUEST_SIZE);
+        let speak =
+            Speak::text_with_voice_settings(&EnUsVoices::GuyNeural.to_voice_settings(), &text);
+        assert!(speak.validate().is_err());
+    }
+
+    #[test]
+    fn typed_model_out_of_range() {
+        let ssml = "<speak xml:lang=\"en-US\"><voice name=\"a\">\
+<mstts:express-as style=\"sad\" styledegree=\"5\">x</mstts:express-as></voice></speak>";
+        assert!(validate_ssml(ssml).is_err());
+        // the typed model can't hold values out of range
+        assert!(Speak::from_ssml_str(ssml).is_err());
+        let expression = Expression::new(Style::Sad).with_style_degree(2.0).unwrap();
+        let speak = Speak::segments_with_voice_settings(
+            &EnUsVoices::SaraNeural.to_voice_settings(),
+            vec![VoiceSegment::with_expression("x", expression)],
+        );
+        assert!(speak.validate().is_ok());
+    }
+}
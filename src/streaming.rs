@@ -0,0 +1,243 @@
+/// Buffering of streamed text, such as tokens of a language model, into phrases worth synthesizing.
+use crate::{Result, SentenceSegmenter};
+use futures::{stream, Future, Stream, StreamExt};
+
+const DEFAULT_CLAUSE_LENGTH: usize = 100;
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+/// Text without any punctuation is cut between words after this many clause lengths
+const MAX_PHRASE_CLAUSES: usize = 4;
+
+/// Settings for [`crate::VoiceService::synthesize_stream`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamingSettings {
+    /// Length in bytes after which text is sent at a clause boundary instead of waiting for the end of the sentence
+    pub clause_length: usize,
+    pub max_concurrent_requests: usize,
+}
+
+impl Default for StreamingSettings {
+    fn default() -> Self {
+        Self {
+            clause_length: DEFAULT_CLAUSE_LENGTH,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+        }
+    }
+}
+
+impl StreamingSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_clause_length(mut self, clause_length: usize) -> Self {
+        self.clause_length = clause_length.max(1);
+        self
+    }
+
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests.max(1);
+        self
+    }
+}
+
+fn is_clause_end(character: char) -> bool {
+    matches!(character, ',' | ';' | ':' | '—' | '，' | '、' | '；' | '：')
+}
+
+/// Collects text until a phrase is complete
+pub(crate) struct PhraseBuffer {
+    segmenter: SentenceSegmenter,
+    clause_length: usize,
+    buffer: String,
+}
+
+impl PhraseBuffer {
+    pub(crate) fn new(segmenter: SentenceSegmenter, clause_length: usize) -> Self {
+        Self {
+            segmenter,
+            clause_length,
+            buffer: String::new(),
+        }
+    }
+
+    /// Add text and take the phrases it completed
+    pub(crate) fn push(&mut self, text: &str) -> Vec<String> {
+        self.buffer.push_str(text);
+        let mut phrases = vec![];
+        let sentences = self.segmenter.sentences(&self.buffer);
+        // the last sentence may still continue, for example after an abbreviation
+        if let Some((last, complete)) = sentences.split_last() {
+            if !complete.is_empty() {
+                phrases.extend(complete.iter().map(|sentence| sentence.to_string()));
+                let rest = last.as_ptr() as usize - self.buffer.as_ptr() as usize;
+                self.buffer.drain(..rest);
+            }
+        }
+        while let Some(end) = self.clause_cut() {
+            let phrase = self.buffer[..end].trim().to_owned();
+            self.buffer.drain(..end);
+            if !phrase.is_empty() {
+                phrases.push(phrase);
+            }
+        }
+        phrases
+    }
+
+    /// Take what remains once the input ended
+    pub(crate) fn finish(&mut self) -> Option<String> {
+        let phrase = self.buffer.trim().to_owned();
+        self.buffer.clear();
+        Some(phrase).filter(|phrase| !phrase.is_empty())
+    }
+
+    /// Where to cut a long unfinished sentence, after the last clause punctuation or word
+    fn clause_cut(&self) -> Option<usize> {
+        if self.buffer.len() <= self.clause_length {
+            return None;
+        }
+        let clause = self
+            .buffer
+            .char_indices()
+            .zip(self.buffer.chars().skip(1))
+            .filter(|((_, character), next)| {
+                is_clause_end(*character) && (next.is_whitespace() || !character.is_ascii())
+            })
+            .map(|((index, character), _)| index + character.len_utf8())
+            .last();
+        match clause {
+            Some(end) => Some(end),
+            None if self.buffer.len() > self.clause_length * MAX_PHRASE_CLAUSES => self
+                .buffer
+                .rfind(char::is_whitespace)
+                .filter(|end| *end > 0),
+            None => None,
+        }
+    }
+}
+
+/// Phrases of streamed text in order, the rest of the text is flushed when the input ends
+pub(crate) fn phrases<S>(
+    text: S,
+    segmenter: SentenceSegmenter,
+    clause_length: usize,
+) -> impl Stream<Item = String>
+where
+    S: Stream<Item = String>,
+{
+    let state = Some((Box::pin(text), PhraseBuffer::new(segmenter, clause_length)));
+    stream::unfold(state, |state| async move {
+        let (mut text, mut buffer) = state?;
+        loop {
+            match text.next().await {
+                Some(chunk) => {
+                    let phrases = buffer.push(&chunk);
+                    if !phrases.is_empty() {
+                        return Some((stream::iter(phrases), Some((text, buffer))));
+                    }
+                }
+                None => {
+                    let rest = buffer.finish().into_iter().collect::<Vec<_>>();
+                    return Some((stream::iter(rest), None));
+                }
+            }
+        }
+    })
+    .flatten()
+}
+
+/// Audio of each phrase in order, up to `max_concurrent_requests` phrases are synthesized at once
+///
+/// A phrase that fails yields its error in place, later phrases are still synthesized.
+pub(crate) fn synthesize_phrases<P, F, A>(
+    phrases: P,
+    max_concurrent_requests: usize,
+    synthesize: F,
+) -> impl Stream<Item = Result<Vec<u8>>>
+where
+    P: Stream<Item = String>,
+    F: FnMut(String) -> A,
+    A: Future<Output = Result<Vec<u8>>>,
+{
+    phrases
+        .map(synthesize)
+        .buffered(max_concurrent_requests.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Locale, TtsError};
+
+    fn english_buffer() -> PhraseBuffer {
+        PhraseBuffer::new(SentenceSegmenter::for_language(&Locale::en_us()), 20)
+    }
+
+    #[test]
+    fn waits_for_sentence_to_continue() {
+        let mut buffer = english_buffer();
+        assert!(buffer.push("Hello Dr.").is_empty());
+        assert_eq!(vec!["Hello Dr. Who.".to_owned()], buffer.push(" Who. How"));
+        assert!(buffer.push(" are you?").is_empty());
+        assert_eq!(Some("How are you?".to_owned()), buffer.finish());
+        assert_eq!(None, buffer.finish());
+    }
+
+    #[test]
+    fn long_sentences_are_cut_at_clauses() {
+        let mut buffer = english_buffer();
+        assert!(buffer.push("First part, second").is_empty());
+        assert_eq!(vec!["First part,".to_owned()], buffer.push(" part and"));
+        assert_eq!(Some("second part and".to_owned()), buffer.finish());
+
+        let mut buffer = english_buffer();
+        let phrases = buffer.push(
+            "one two three four five six seven eight nine ten eleven twelve thirteen fourteen fifteen",
+        );
+        assert_eq!(
+            vec![
+                "one two three four five six seven eight nine ten eleven twelve thirteen fourteen"
+                    .to_owned()
+            ],
+            phrases
+        );
+        assert_eq!(Some("fifteen".to_owned()), buffer.finish());
+    }
+
+    #[tokio::test]
+    async fn phrases_of_stream() {
+        let tokens = stream::iter(
+            ["The ", "sky", " is", " blue.", " Grass", " is", " green"]
+                .iter()
+                .map(|token| token.to_string()),
+        );
+        let phrases: Vec<String> = phrases(tokens, SentenceSegmenter::default(), 100)
+            .collect()
+            .await;
+        assert_eq!(
+            vec!["The sky is blue.".to_owned(), "Grass is green".to_owned()],
+            phrases
+        );
+    }
+
+    #[tokio::test]
+    async fn phrase_failing_mid_stream() {
+        let tokens = stream::iter(
+            ["One. ", "Two. ", "Three."]
+                .iter()
+                .map(|token| token.to_string()),
+        );
+        let phrases = phrases(tokens, SentenceSegmenter::default(), 100);
+        let audio: Vec<Result<Vec<u8>>> = synthesize_phrases(phrases, 2, |phrase| async move {
+            match phrase.as_str() {
+                "Two." => Err(TtsError::TooManyRequest),
+                _ => Ok(phrase.into_bytes()),
+            }
+        })
+        .collect()
+        .await;
+        assert_eq!(3, audio.len());
+        assert_eq!(b"One.".to_vec(), *audio[0].as_ref().unwrap());
+        assert!(matches!(audio[1], Err(TtsError::TooManyRequest)));
+        assert_eq!(b"Three.".to_vec(), *audio[2].as_ref().unwrap());
+    }
+}
@@ -1,17 +1,142 @@
-use serde::{Deserialize, Serialize};
+use crate::{Locale, TtsError};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::HashMap, convert::Infallible, fmt, str::FromStr};
+
+/// Enum of the values known when it was written and `Other` for any other value
+///
+/// Parsing is case insensitive and never fails, values are displayed and serialized as the
+/// string the service uses.
+macro_rules! open_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $value:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            Other(String),
+        }
+
+        impl $name {
+            /// Every value except `Other`
+            pub(crate) const KNOWN: &'static [$name] = &[$($name::$variant),*];
+
+            pub fn as_string(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Other(value) => value,
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Infallible;
+
+            /// Case insensitive. Unknown values are kept as `Other`
+            fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+                let known = Self::KNOWN
+                    .iter()
+                    .find(|known| known.as_string().eq_ignore_ascii_case(value))
+                    .cloned()
+                    .unwrap_or_else(|| $name::Other(value.to_owned()));
+                Ok(known)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_string())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_string())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                Ok(value.parse().unwrap_or_else(|never| match never {}))
+            }
+        }
+    };
+}
+
+/// Enum of a fixed set of ssml values
+///
+/// Parsing is case insensitive and fails with [`TtsError::InvalidValue`] for unknown values,
+/// alternative names are accepted after the name that's written.
+macro_rules! closed_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident($description:literal) {
+            $($(#[$variant_meta:meta])* $variant:ident => $value:literal $(| $alias:literal)*,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+        }
+
+        impl $name {
+            pub fn as_string(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $value,)*
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = TtsError;
+
+            /// Case insensitive
+            fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+                $(
+                    if [$value $(, $alias)*]
+                        .iter()
+                        .any(|name| name.eq_ignore_ascii_case(value))
+                    {
+                        return Ok($name::$variant);
+                    }
+                )*
+                Err(TtsError::InvalidValue(format!(
+                    concat!("unknown ", $description, " {:?}"),
+                    value
+                )))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_string())
+            }
+        }
+    };
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VoiceSettings {
     pub name: String,
-    pub language: String,
+    pub language: Locale,
     pub gender: VoiceGender,
 }
 
 impl VoiceSettings {
-    pub fn new(name: &str, language: &str, gender: VoiceGender) -> Self {
+    pub fn new(name: &str, language: Locale, gender: VoiceGender) -> Self {
         Self {
             name: name.to_owned(),
-            language: language.to_owned(),
+            language,
             gender,
         }
     }
@@ -19,26 +144,27 @@ impl VoiceSettings {
     pub fn default_female_jenny() -> Self {
         Self {
             name: String::from("en-US-JennyNeural"),
-            language: String::from("en-US"),
+            language: Locale::en_us(),
             gender: VoiceGender::Female,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
-pub enum VoiceGender {
-    // TODO: Other values?
-    #[serde(rename = "Male")]
-    Male,
-    #[serde(rename = "Female")]
-    Female,
+open_enum! {
+    /// Values the service added after this list was written are kept as [`VoiceGender::Other`].
+    pub enum VoiceGender {
+        Male => "Male",
+        Female => "Female",
+        Neutral => "Neutral",
+    }
 }
 
 impl VoiceGender {
-    pub(crate) fn as_string(&self) -> &'static str {
+    /// Value of the ssml `xml:gender` attribute, which has no value for other genders
+    pub(crate) fn xml_gender(&self) -> Option<String> {
         match self {
-            VoiceGender::Female => "Female",
-            VoiceGender::Male => "Male",
+            VoiceGender::Other(_) => None,
+            gender => Some(gender.as_string().to_owned()),
         }
     }
 }
@@ -56,23 +182,135 @@ pub struct VoiceDescription {
     #[serde(alias = "Gender")]
     pub gender: VoiceGender,
     #[serde(alias = "Locale")]
-    pub locale: String,
+    pub locale: Locale,
+    /// Name of the locale such as `English (United States)`
+    #[serde(alias = "LocaleName", default)]
+    pub locale_name: Option<String>,
     #[serde(alias = "SecondaryLocaleList")]
-    pub secondary_locale_list: Option<Vec<String>>,
+    pub secondary_locale_list: Option<Vec<Locale>>,
     #[serde(alias = "StyleList")]
     pub style_list: Option<Vec<String>>,
-    #[serde(alias = "SampleRateHertz")]
-    // TODO: Why is this a string?
-    pub sample_rate_hertz: String,
+    #[serde(alias = "RolePlayList", default)]
+    pub role_play_list: Option<Vec<String>>,
+    #[serde(alias = "SampleRateHertz", deserialize_with = "deserialize_number")]
+    pub sample_rate_hertz: u32,
     #[serde(alias = "VoiceType")]
-    pub voice_type: String,
+    pub voice_type: VoiceType,
     #[serde(alias = "Status")]
-    pub status: String,
+    pub status: VoiceStatus,
+    #[serde(
+        alias = "WordsPerMinute",
+        default,
+        deserialize_with = "deserialize_optional_number"
+    )]
+    pub words_per_minute: Option<u32>,
+    /// Additional properties such as `IsHighQuality48K`
+    #[serde(alias = "ExtendedPropertyMap", default)]
+    pub extended_property_map: Option<HashMap<String, serde_json::Value>>,
+    #[serde(alias = "VoiceTag", default)]
+    pub voice_tag: Option<VoiceTag>,
+}
+
+/// Scenarios and personalities a voice is tailored to
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct VoiceTag {
+    #[serde(alias = "TailoredScenarios", default)]
+    pub tailored_scenarios: Vec<String>,
+    #[serde(alias = "VoicePersonalities", default)]
+    pub voice_personalities: Vec<String>,
+}
+
+/// The voice list sends numbers as strings
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Number(u32),
+    Text(String),
+}
+
+impl Number {
+    fn value(self) -> Option<u32> {
+        match self {
+            Number::Number(number) => Some(number),
+            Number::Text(text) => text.trim().parse().ok(),
+        }
+    }
+}
+
+fn deserialize_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<u32, D::Error> {
+    Number::deserialize(deserializer)?
+        .value()
+        .ok_or_else(|| de::Error::custom("expected a number"))
+}
+
+/// Missing or malformed values become `None`
+fn deserialize_optional_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<u32>, D::Error> {
+    Ok(Option::<Number>::deserialize(deserializer)
+        .ok()
+        .flatten()
+        .and_then(Number::value))
 }
 
 impl VoiceDescription {
+    /// Parse entries of the voice list, returns the voices and the entries that don't parse
+    pub fn from_entries(entries: Vec<serde_json::Value>) -> (Vec<Self>, Vec<serde_json::Value>) {
+        let mut voices = vec![];
+        let mut skipped = vec![];
+        for entry in entries {
+            match serde_json::from_value(entry.clone()) {
+                Ok(voice) => voices.push(voice),
+                Err(_) => skipped.push(entry),
+            }
+        }
+        (voices, skipped)
+    }
+
     pub fn to_voice_settings(&self) -> VoiceSettings {
-        VoiceSettings::new(&self.short_name, &self.local_name, self.gender)
+        VoiceSettings::new(&self.short_name, self.locale.clone(), self.gender.clone())
+    }
+
+    /// Styles supported by this voice
+    pub fn styles(&self) -> Vec<Style> {
+        self.style_list
+            .iter()
+            .flatten()
+            .map(|style| style.parse().unwrap_or_else(|never| match never {}))
+            .collect()
+    }
+
+    /// Roles supported by this voice, roles this crate doesn't know are skipped
+    pub fn roles(&self) -> Vec<Role> {
+        self.role_play_list
+            .iter()
+            .flatten()
+            .filter_map(|role| role.parse().ok())
+            .collect()
+    }
+}
+
+open_enum! {
+    /// Kind of voice model
+    ///
+    /// Types the service added after this list was written are kept as [`VoiceType::Other`].
+    pub enum VoiceType {
+        Neural => "Neural",
+        Standard => "Standard",
+    }
+}
+
+open_enum! {
+    /// Release status of a voice
+    ///
+    /// Statuses the service added after this list was written are kept as [`VoiceStatus::Other`].
+    pub enum VoiceStatus {
+        /// `GA`
+        GeneralAvailability => "GA",
+        Preview => "Preview",
+        Deprecated => "Deprecated",
     }
 }
 
@@ -217,109 +455,394 @@ impl EnUsVoices {
     pub fn to_voice_settings(&self) -> VoiceSettings {
         match self {
             EnUsVoices::JennyNeural => {
-                VoiceSettings::new("en-US-JennyNeural", "en-US", VoiceGender::Female)
+                VoiceSettings::new("en-US-JennyNeural", Locale::en_us(), VoiceGender::Female)
             }
             EnUsVoices::JennyMultilingualNeural => VoiceSettings::new(
                 "en-US-JennyMultilingualNeural",
-                "en-US",
+                Locale::en_us(),
                 VoiceGender::Female,
             ),
             EnUsVoices::GuyNeural => {
-                VoiceSettings::new("en-US-GuyNeural", "en-US", VoiceGender::Male)
+                VoiceSettings::new("en-US-GuyNeural", Locale::en_us(), VoiceGender::Male)
             }
             EnUsVoices::AmberNeural => {
-                VoiceSettings::new("en-US-AmberNeural", "en-US", VoiceGender::Female)
+                VoiceSettings::new("en-US-AmberNeural", Locale::en_us(), VoiceGender::Female)
             }
             EnUsVoices::AnaNeural => {
-                VoiceSettings::new("en-US-AnaNeural", "en-US", VoiceGender::Female)
+                VoiceSettings::new("en-US-AnaNeural", Locale::en_us(), VoiceGender::Female)
             }
             EnUsVoices::AriaNeural => {
-                VoiceSettings::new("en-US-AriaNeural", "en-US", VoiceGender::Female)
+                VoiceSettings::new("en-US-AriaNeural", Locale::en_us(), VoiceGender::Female)
             }
             EnUsVoices::AshleyNeural => {
-                VoiceSettings::new("en-US-AshleyNeural", "en-US", VoiceGender::Female)
+                VoiceSettings::new("en-US-AshleyNeural", Locale::en_us(), VoiceGender::Female)
             }
             EnUsVoices::BrandonNeural => {
-                VoiceSettings::new("en-US-BrandonNeural", "en-US", VoiceGender::Male)
-            }
-            EnUsVoices::ChristopherNeural => {
-                VoiceSettings::new("en-US-ChristopherNeural", "en-US", VoiceGender::Male)
+                VoiceSettings::new("en-US-BrandonNeural", Locale::en_us(), VoiceGender::Male)
             }
+            EnUsVoices::ChristopherNeural => VoiceSettings::new(
+                "en-US-ChristopherNeural",
+                Locale::en_us(),
+                VoiceGender::Male,
+            ),
             EnUsVoices::CoraNeural => {
-                VoiceSettings::new("en-US-CoraNeural", "en-US", VoiceGender::Female)
-            }
-            EnUsVoices::ElizabethNeural => {
-                VoiceSettings::new("en-US-ElizabethNeural", "en-US", VoiceGender::Female)
+                VoiceSettings::new("en-US-CoraNeural", Locale::en_us(), VoiceGender::Female)
             }
+            EnUsVoices::ElizabethNeural => VoiceSettings::new(
+                "en-US-ElizabethNeural",
+                Locale::en_us(),
+                VoiceGender::Female,
+            ),
             EnUsVoices::EricNeural => {
-                VoiceSettings::new("en-US-EricNeural", "en-US", VoiceGender::Male)
+                VoiceSettings::new("en-US-EricNeural", Locale::en_us(), VoiceGender::Male)
             }
             EnUsVoices::JacobNeural => {
-                VoiceSettings::new("en-US-JacobNeural", "en-US", VoiceGender::Male)
+                VoiceSettings::new("en-US-JacobNeural", Locale::en_us(), VoiceGender::Male)
             }
             EnUsVoices::MichelleNeural => {
-                VoiceSettings::new("en-US-MichelleNeural", "en-US", VoiceGender::Female)
+                VoiceSettings::new("en-US-MichelleNeural", Locale::en_us(), VoiceGender::Female)
             }
             EnUsVoices::MonicaNeural => {
-                VoiceSettings::new("en-US-MonicaNeural", "en-US", VoiceGender::Female)
+                VoiceSettings::new("en-US-MonicaNeural", Locale::en_us(), VoiceGender::Female)
             }
             EnUsVoices::SaraNeural => {
-                VoiceSettings::new("en-US-SaraNeural", "en-US", VoiceGender::Female)
+                VoiceSettings::new("en-US-SaraNeural", Locale::en_us(), VoiceGender::Female)
             }
         }
     }
 }
 
-#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Style {
-    #[serde(rename = "$primitive=affectionate")]
-    Affectionate,
-    #[serde(rename = "$primitive=angry")]
-    Angry,
-    #[serde(rename = "$primitive=assistant")]
-    Assistant,
-    #[serde(rename = "$primitive=calm")]
-    Calm,
-    #[serde(rename = "$primitive=chat")]
-    Chat,
-    #[serde(rename = "$primitive=cheerful")]
-    Cheerful,
-    #[serde(rename = "$primitive=customerservice")]
-    Customerservice,
-    #[serde(rename = "$primitive=disgruntled")]
-    Disgruntled,
-    #[serde(rename = "$primitive=empathetic")]
-    Empathetic,
-    #[serde(rename = "$primitive=fearful")]
-    Fearful,
-    #[serde(rename = "$primitive=gentle")]
-    Gentle,
-    #[serde(rename = "$primitive=lyrical")]
-    Lyrical,
-    #[serde(rename = "$primitive=narration-professional")]
-    NarrationProfessional,
-    #[serde(rename = "$primitive=newscast")]
-    Newscast,
-    #[serde(rename = "$primitive=newscast-casual")]
-    NewscastCasual,
-    #[serde(rename = "$primitive=newscast-formal")]
-    NewscastFormal,
-    #[serde(rename = "$primitive=sad")]
-    Sad,
-    #[serde(rename = "$primitive=serious")]
-    Serious,
-    #[serde(rename = "$primitive=depressed")]
-    Depressed,
-    #[serde(rename = "$primitive=embarrassed")]
-    Embarrassed,
+open_enum! {
+    /// Speaking style used by express-as
+    ///
+    /// Styles the service added after this list was written can be used through [`Style::Other`].
+    pub enum Style {
+        Affectionate => "affectionate",
+        Angry => "angry",
+        Assistant => "assistant",
+        Calm => "calm",
+        Chat => "chat",
+        Cheerful => "cheerful",
+        Customerservice => "customerservice",
+        Disgruntled => "disgruntled",
+        Empathetic => "empathetic",
+        Fearful => "fearful",
+        Gentle => "gentle",
+        Lyrical => "lyrical",
+        NarrationProfessional => "narration-professional",
+        Newscast => "newscast",
+        NewscastCasual => "newscast-casual",
+        NewscastFormal => "newscast-formal",
+        Sad => "sad",
+        Serious => "serious",
+        Depressed => "depressed",
+        Embarrassed => "embarrassed",
+        Excited => "excited",
+        Friendly => "friendly",
+        Hopeful => "hopeful",
+        Shouting => "shouting",
+        Whispering => "whispering",
+        Terrified => "terrified",
+        Unfriendly => "unfriendly",
+        Envious => "envious",
+        PoetryReading => "poetry-reading",
+        NarrationRelaxed => "narration-relaxed",
+        DocumentaryNarration => "documentary-narration",
+        AdvertisementUpbeat => "advertisement_upbeat",
+        SportsCommentary => "sports_commentary",
+        SportsCommentaryExcited => "sports_commentary_excited",
+    }
+}
+
+closed_enum! {
+    /// Role-play for express-as. Voice imitates a different age and gender
+    pub enum Role("role") {
+        Girl => "Girl",
+        Boy => "Boy",
+        YoungAdultFemale => "YoungAdultFemale",
+        YoungAdultMale => "YoungAdultMale",
+        OlderAdultFemale => "OlderAdultFemale",
+        OlderAdultMale => "OlderAdultMale",
+        SeniorFemale => "SeniorFemale",
+        SeniorMale => "SeniorMale",
+    }
+}
+
+open_enum! {
+    /// Type of silence inserted by mstts:silence
+    ///
+    /// Types the service added after this list was written can be used through
+    /// [`SilenceAttributeType::Other`].
+    pub enum SilenceAttributeType {
+        Leading => "Leading",
+        Tailing => "Tailing",
+        Sentenceboundary => "Sentenceboundary",
+        LeadingExact => "Leading-exact",
+        TailingExact => "Tailing-exact",
+        SentenceboundaryExact => "Sentenceboundary-exact",
+        CommaExact => "Comma-exact",
+        SemicolonExact => "Semicolon-exact",
+        EnumerationcommaExact => "Enumerationcomma-exact",
+    }
+}
+
+closed_enum! {
+    pub enum EmphasisLevel("emphasis level") {
+        Strong => "strong",
+        Moderate => "moderate",
+        None => "none",
+        Reduced => "reduced",
+    }
+}
+
+closed_enum! {
+    pub enum BreakStrength("break strength") {
+        XWeak => "x-weak",
+        Weak => "weak",
+        Medium => "medium",
+        Strong => "strong",
+        XStrong => "x-strong",
+    }
+}
+
+open_enum! {
+    /// Content type used by say-as
+    ///
+    /// Values the service added after this list was written can be used through
+    /// [`InterpretAs::Other`].
+    pub enum InterpretAs {
+        Address => "address",
+        Cardinal => "cardinal",
+        Characters => "characters",
+        SpellOut => "spell-out",
+        Currency => "currency",
+        Date => "date",
+        Digits => "digits",
+        Duration => "duration",
+        Fraction => "fraction",
+        Name => "name",
+        Ordinal => "ordinal",
+        Telephone => "telephone",
+        Time => "time",
+    }
+}
+
+closed_enum! {
+    pub enum PhoneticAlphabet("phonetic alphabet") {
+        Ipa => "ipa",
+        Sapi => "sapi" | "x-microsoft-sapi",
+        Ups => "ups" | "x-microsoft-ups",
+        XSampa => "x-sampa",
+    }
+}
+
+impl PhoneticAlphabet {
+    /// Name used by pls lexicon documents, which prefix the microsoft alphabets
+    pub(crate) fn as_pls_string(&self) -> &'static str {
+        match self {
+            PhoneticAlphabet::Sapi => "x-microsoft-sapi",
+            PhoneticAlphabet::Ups => "x-microsoft-ups",
+            alphabet => alphabet.as_string(),
+        }
+    }
 }
 
-#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
-pub enum SilenceAttributeType {
-    #[serde(rename = "$primitive=Leading")]
-    Leading,
-    #[serde(rename = "$primitive=Tailing")]
-    Tailing,
-    #[serde(rename = "$primitive=Sentenceboundary")]
-    Sentenceboundary,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn style_parsing() {
+        assert_eq!(Style::Cheerful, "cheerful".parse().unwrap());
+        assert_eq!(Style::Cheerful, "Cheerful".parse().unwrap());
+        assert_eq!(Style::PoetryReading, "poetry-reading".parse().unwrap());
+        assert_eq!(
+            Style::SportsCommentary,
+            "sports_commentary".parse().unwrap()
+        );
+        assert_eq!(
+            Style::Other("future-style".to_owned()),
+            "future-style".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn style_display_round_trip() {
+        for style in Style::KNOWN {
+            assert_eq!(style, &style.to_string().parse::<Style>().unwrap());
+        }
+        assert_eq!("custom", Style::Other("custom".to_owned()).to_string());
+    }
+
+    #[test]
+    fn closed_enum_parsing() {
+        assert_eq!(Role::SeniorMale, "seniormale".parse().unwrap());
+        assert_eq!(BreakStrength::XStrong, "x-strong".parse().unwrap());
+        assert_eq!(PhoneticAlphabet::Sapi, "x-microsoft-sapi".parse().unwrap());
+        assert_eq!("sapi", PhoneticAlphabet::Sapi.to_string());
+        match "loud".parse::<EmphasisLevel>() {
+            Err(TtsError::InvalidValue(message)) => {
+                assert_eq!("unknown emphasis level \"loud\"", message)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn style_serde() {
+        let styles: Vec<Style> =
+            serde_json::from_str(r#"["newscast-casual", "whispering", "singing"]"#).unwrap();
+        assert_eq!(
+            vec![
+                Style::NewscastCasual,
+                Style::Whispering,
+                Style::Other("singing".to_owned())
+            ],
+            styles
+        );
+        assert_eq!(
+            r#"["newscast-casual","whispering","singing"]"#,
+            serde_json::to_string(&styles).unwrap()
+        );
+    }
+
+    #[test]
+    fn silence_attribute_type_parsing() {
+        assert_eq!(
+            SilenceAttributeType::Sentenceboundary,
+            "sentenceboundary".parse().unwrap()
+        );
+        assert_eq!(
+            SilenceAttributeType::Other("Paragraph".to_owned()),
+            "Paragraph".parse().unwrap()
+        );
+        assert_eq!(
+            SilenceAttributeType::EnumerationcommaExact,
+            "Enumerationcomma-exact".parse().unwrap()
+        );
+        assert_eq!("Leading", SilenceAttributeType::Leading.to_string());
+        assert_eq!("Comma-exact", SilenceAttributeType::CommaExact.to_string());
+    }
+
+    #[test]
+    fn voice_description_styles() {
+        let voice: VoiceDescription = serde_json::from_str(
+            r#"{
+                "Name": "Microsoft Server Speech Text to Speech Voice (en-US, JennyNeural)",
+                "DisplayName": "Jenny",
+                "LocalName": "Jenny",
+                "ShortName": "en-US-JennyNeural",
+                "Gender": "Female",
+                "Locale": "en-US",
+                "StyleList": ["assistant", "chat", "excited", "unfriendly"],
+                "SampleRateHertz": "24000",
+                "VoiceType": "Neural",
+                "Status": "GA"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                Style::Assistant,
+                Style::Chat,
+                Style::Excited,
+                Style::Unfriendly
+            ],
+            voice.styles()
+        );
+        assert_eq!("en-US", voice.to_voice_settings().language.as_str());
+        assert_eq!(24000, voice.sample_rate_hertz);
+        assert_eq!(None, voice.voice_tag);
+    }
+
+    #[test]
+    fn voice_list_with_new_and_broken_entries() {
+        let entries: Vec<serde_json::Value> = serde_json::from_str(
+            r#"[
+                {
+                    "Name": "Microsoft Server Speech Text to Speech Voice (en-US, AvaMultilingualNeural)",
+                    "DisplayName": "Ava",
+                    "LocalName": "Ava",
+                    "ShortName": "en-US-AvaMultilingualNeural",
+                    "Gender": "Neutral",
+                    "Locale": "en-US",
+                    "SampleRateHertz": "24000",
+                    "VoiceType": "Neural",
+                    "Status": "GA"
+                },
+                {
+                    "Name": "Future voice",
+                    "DisplayName": "Sky",
+                    "LocalName": "Sky",
+                    "ShortName": "en-US-SkyNeural",
+                    "Gender": "Nonbinary",
+                    "Locale": "en-US",
+                    "SampleRateHertz": "24000",
+                    "VoiceType": "Neural",
+                    "Status": "Preview"
+                },
+                { "Name": "Broken voice" }
+            ]"#,
+        )
+        .unwrap();
+        let (voices, skipped) = VoiceDescription::from_entries(entries);
+        assert_eq!(2, voices.len());
+        assert_eq!(vec![serde_json::json!({ "Name": "Broken voice" })], skipped);
+        assert_eq!(VoiceGender::Neutral, voices[0].gender);
+        assert_eq!(VoiceGender::Other("Nonbinary".to_owned()), voices[1].gender);
+        assert_eq!(VoiceGender::Female, "female".parse().unwrap());
+    }
+
+    #[test]
+    fn voice_description_all_fields() {
+        let voice: VoiceDescription = serde_json::from_str(
+            r#"{
+                "Name": "Microsoft Server Speech Text to Speech Voice (en-US, AriaNeural)",
+                "DisplayName": "Aria",
+                "LocalName": "Aria",
+                "ShortName": "en-US-AriaNeural",
+                "Gender": "Female",
+                "Locale": "en-US",
+                "LocaleName": "English (United States)",
+                "SecondaryLocaleList": ["de-DE"],
+                "StyleList": ["chat"],
+                "RolePlayList": ["Girl", "FutureRole", "SeniorMale"],
+                "SampleRateHertz": 48000,
+                "VoiceType": "NeuralHD",
+                "Status": "GA",
+                "ExtendedPropertyMap": { "IsHighQuality48K": "True" },
+                "WordsPerMinute": "150",
+                "VoiceTag": {
+                    "TailoredScenarios": ["Chat", "Assistant"],
+                    "VoicePersonalities": ["Warm"]
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Some("English (United States)"),
+            voice.locale_name.as_deref()
+        );
+        assert_eq!(vec![Role::Girl, Role::SeniorMale], voice.roles());
+        assert_eq!(48000, voice.sample_rate_hertz);
+        assert_eq!(VoiceType::Other("NeuralHD".to_owned()), voice.voice_type);
+        assert_eq!(VoiceStatus::GeneralAvailability, voice.status);
+        assert_eq!(Some(150), voice.words_per_minute);
+        assert_eq!(
+            Some(&serde_json::Value::from("True")),
+            voice
+                .extended_property_map
+                .as_ref()
+                .and_then(|properties| properties.get("IsHighQuality48K"))
+        );
+        assert_eq!(
+            Some(VoiceTag {
+                tailored_scenarios: vec!["Chat".to_owned(), "Assistant".to_owned()],
+                voice_personalities: vec!["Warm".to_owned()],
+            }),
+            voice.voice_tag
+        );
+        assert_eq!(VoiceStatus::Preview, "preview".parse().unwrap());
+        assert_eq!("GA", VoiceStatus::GeneralAvailability.to_string());
+    }
 }
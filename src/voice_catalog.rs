@@ -0,0 +1,345 @@
+/// Queries over the voices returned by [`crate::VoiceService::list_voices`].
+use crate::{Locale, Role, Style, VoiceDescription, VoiceGender, VoiceStatus, VoiceType};
+
+/// Misspellings tolerated by [`VoiceCatalog::find`]
+const MAX_EDIT_DISTANCE: usize = 2;
+
+/// Voices of a region
+///
+/// ```
+/// # fn voices() -> Vec<azure_tts::VoiceDescription> { vec![] }
+/// use azure_tts::{Style, VoiceCatalog, VoiceGender};
+///
+/// let catalog = VoiceCatalog::new(voices());
+/// let cheerful = catalog
+///     .query()
+///     .with_locale("en-US".parse().unwrap())
+///     .with_gender(VoiceGender::Female)
+///     .with_style(Style::Cheerful)
+///     .voices();
+/// let jenny = catalog.find("jenny");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VoiceCatalog {
+    voices: Vec<VoiceDescription>,
+}
+
+impl From<Vec<VoiceDescription>> for VoiceCatalog {
+    fn from(voices: Vec<VoiceDescription>) -> Self {
+        Self::new(voices)
+    }
+}
+
+impl VoiceCatalog {
+    pub fn new(voices: Vec<VoiceDescription>) -> Self {
+        Self { voices }
+    }
+
+    pub fn voices(&self) -> &[VoiceDescription] {
+        &self.voices
+    }
+
+    /// Query matching all voices until narrowed down
+    pub fn query(&self) -> VoiceQuery<'_> {
+        VoiceQuery {
+            voices: &self.voices,
+            locale: None,
+            gender: None,
+            style: None,
+            role: None,
+            voice_type: None,
+            status: None,
+            name: None,
+        }
+    }
+
+    /// Voice best matching a name such as `jenny`, `JennyNeural`, `en-US-JennyNeural` or a misspelling like `jeny`
+    ///
+    /// Exact names win over voices starting with or containing the name, the first voice of the catalog wins ties.
+    pub fn find(&self, name: &str) -> Option<&VoiceDescription> {
+        let wanted = normalize(name);
+        if wanted.is_empty() {
+            return None;
+        }
+        self.voices
+            .iter()
+            .filter_map(|voice| match_score(voice, name, &wanted).map(|score| (score, voice)))
+            .min_by_key(|(score, _)| *score)
+            .map(|(_, voice)| voice)
+    }
+}
+
+/// Voices matching every condition that was set
+#[derive(Debug, Clone)]
+pub struct VoiceQuery<'a> {
+    voices: &'a [VoiceDescription],
+    locale: Option<Locale>,
+    gender: Option<VoiceGender>,
+    style: Option<Style>,
+    role: Option<Role>,
+    voice_type: Option<VoiceType>,
+    status: Option<VoiceStatus>,
+    name: Option<String>,
+}
+
+impl<'a> VoiceQuery<'a> {
+    /// Voices speaking a locale as primary or secondary locale. A language such as `en` matches all of its regions
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    pub fn with_gender(mut self, gender: VoiceGender) -> Self {
+        self.gender = Some(gender);
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    pub fn with_voice_type(mut self, voice_type: VoiceType) -> Self {
+        self.voice_type = Some(voice_type);
+        self
+    }
+
+    pub fn with_status(mut self, status: VoiceStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Case insensitive part of the short, display or local name
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_lowercase());
+        self
+    }
+
+    pub fn voices(&self) -> Vec<&'a VoiceDescription> {
+        self.voices
+            .iter()
+            .filter(|voice| self.matches(voice))
+            .collect()
+    }
+
+    pub fn first(&self) -> Option<&'a VoiceDescription> {
+        self.voices.iter().find(|voice| self.matches(voice))
+    }
+
+    fn matches(&self, voice: &VoiceDescription) -> bool {
+        let locale = self.locale.as_ref().is_none_or(|locale| {
+            std::iter::once(&voice.locale)
+                .chain(voice.secondary_locale_list.iter().flatten())
+                .any(|voice_locale| locale.includes(voice_locale))
+        });
+        let name = self.name.as_ref().is_none_or(|name| {
+            [&voice.short_name, &voice.display_name, &voice.local_name]
+                .iter()
+                .any(|voice_name| voice_name.to_lowercase().contains(name))
+        });
+        locale
+            && name
+            && self
+                .gender
+                .as_ref()
+                .is_none_or(|gender| voice.gender == *gender)
+            && self
+                .style
+                .as_ref()
+                .is_none_or(|style| voice.styles().contains(style))
+            && self.role.is_none_or(|role| voice.roles().contains(&role))
+            && self
+                .voice_type
+                .as_ref()
+                .is_none_or(|voice_type| voice.voice_type == *voice_type)
+            && self
+                .status
+                .as_ref()
+                .is_none_or(|status| voice.status == *status)
+    }
+}
+
+/// Lowercase letters and digits without the `Neural` suffix
+fn normalize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|character| character.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    match name.strip_suffix("neural") {
+        Some(stripped) if !stripped.is_empty() => stripped.to_owned(),
+        _ => name,
+    }
+}
+
+/// Lower is better, `None` if the voice doesn't match at all
+fn match_score(voice: &VoiceDescription, name: &str, wanted: &str) -> Option<usize> {
+    if voice.short_name.eq_ignore_ascii_case(name) || voice.name == name {
+        return Some(0);
+    }
+    // name without the locale, `JennyNeural` of `en-US-JennyNeural`
+    let voice_part = voice
+        .short_name
+        .strip_prefix(voice.locale.as_str())
+        .unwrap_or(&voice.short_name);
+    let candidates = [
+        normalize(voice_part),
+        normalize(&voice.short_name),
+        normalize(&voice.display_name),
+        normalize(&voice.local_name),
+    ];
+    let candidates = candidates.iter().filter(|candidate| !candidate.is_empty());
+    candidates
+        .filter_map(|candidate| {
+            if candidate == wanted {
+                Some(1)
+            } else if candidate.starts_with(wanted) {
+                Some(2)
+            } else if candidate.contains(wanted) {
+                Some(3)
+            } else if wanted.chars().count() > MAX_EDIT_DISTANCE * 2 {
+                let distance = edit_distance(candidate, wanted);
+                (distance <= MAX_EDIT_DISTANCE).then_some(3 + distance)
+            } else {
+                None
+            }
+        })
+        .min()
+}
+
+/// Levenshtein distance in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_character) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_character) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_character != *b_character);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voice(short_name: &str, gender: &str, extra: &str) -> VoiceDescription {
+        let (locale, name) = short_name.rsplit_once('-').unwrap();
+        let locale: Locale = locale.parse().unwrap();
+        let name = name.trim_end_matches("Neural");
+        serde_json::from_str(&format!(
+            r#"{{
+                "Name": "Microsoft Server Speech Text to Speech Voice ({locale}, {name}Neural)",
+                "DisplayName": "{name}",
+                "LocalName": "{name}",
+                "ShortName": "{short_name}",
+                "Gender": "{gender}",
+                "Locale": "{locale}",
+                "SampleRateHertz": "24000",
+                "VoiceType": "Neural",
+                "Status": "GA"
+                {extra}
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    fn catalog() -> VoiceCatalog {
+        VoiceCatalog::new(vec![
+            voice(
+                "en-US-JennyMultilingualNeural",
+                "Female",
+                r#", "SecondaryLocaleList": ["de-DE", "fr-FR"]"#,
+            ),
+            voice(
+                "en-US-JennyNeural",
+                "Female",
+                r#", "StyleList": ["cheerful", "sad"]"#,
+            ),
+            voice(
+                "en-GB-RyanNeural",
+                "Male",
+                r#", "StyleList": ["cheerful"], "RolePlayList": ["Boy"]"#,
+            ),
+            VoiceDescription {
+                status: VoiceStatus::Preview,
+                ..voice("de-DE-KatjaNeural", "Female", "")
+            },
+        ])
+    }
+
+    fn short_names(voices: Vec<&VoiceDescription>) -> Vec<&str> {
+        voices
+            .into_iter()
+            .map(|voice| voice.short_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn queries_combine_conditions() {
+        let catalog = catalog();
+        let german: Locale = "de".parse().unwrap();
+        assert_eq!(
+            vec!["en-US-JennyMultilingualNeural", "de-DE-KatjaNeural"],
+            short_names(catalog.query().with_locale(german.clone()).voices())
+        );
+        assert_eq!(
+            vec!["de-DE-KatjaNeural"],
+            short_names(
+                catalog
+                    .query()
+                    .with_locale(german)
+                    .with_status(VoiceStatus::Preview)
+                    .voices()
+            )
+        );
+        assert_eq!(
+            vec!["en-US-JennyNeural"],
+            short_names(
+                catalog
+                    .query()
+                    .with_style(Style::Cheerful)
+                    .with_gender(VoiceGender::Female)
+                    .voices()
+            )
+        );
+        assert_eq!(
+            Some("en-GB-RyanNeural"),
+            catalog
+                .query()
+                .with_role(Role::Boy)
+                .with_voice_type(VoiceType::Neural)
+                .first()
+                .map(|voice| voice.short_name.as_str())
+        );
+        assert_eq!(
+            vec!["en-US-JennyMultilingualNeural"],
+            short_names(catalog.query().with_name("multiLINGUAL").voices())
+        );
+    }
+
+    #[test]
+    fn finds_voices_by_fuzzy_name() {
+        let catalog = catalog();
+        let find = |name: &str| catalog.find(name).map(|voice| voice.short_name.as_str());
+        assert_eq!(Some("en-US-JennyNeural"), find("jenny"));
+        assert_eq!(Some("en-US-JennyNeural"), find("JennyNeural"));
+        assert_eq!(Some("en-US-JennyNeural"), find("en-us-jennyneural"));
+        assert_eq!(
+            Some("en-US-JennyMultilingualNeural"),
+            find("jenny multilingual")
+        );
+        assert_eq!(Some("en-GB-RyanNeural"), find("rya"));
+        assert_eq!(Some("de-DE-KatjaNeural"), find("Katya"));
+        assert_eq!(None, find("Guy"));
+        assert_eq!(None, find(""));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::TtsError;
+
+/// Minimal xml writer shared by the ssml and pls serializers.
+///
+/// Output is written without any indentation so that it can be sent to the service as is.
+pub(crate) struct XmlWriter {
+    output: String,
+}
+
+impl XmlWriter {
+    pub fn new() -> Self {
+        Self {
+            output: String::new(),
+        }
+    }
+
+    pub fn declaration(&mut self) {
+        self.output
+            .push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+    }
+
+    pub fn start(&mut self, name: &str, attributes: &[(&str, &str)]) {
+        self.open_tag(name, attributes);
+        self.output.push('>');
+    }
+
+    pub fn empty(&mut self, name: &str, attributes: &[(&str, &str)]) {
+        self.open_tag(name, attributes);
+        self.output.push_str("/>");
+    }
+
+    pub fn end(&mut self, name: &str) {
+        self.output.push_str("</");
+        self.output.push_str(name);
+        self.output.push('>');
+    }
+
+    pub fn text(&mut self, text: &str) {
+        for character in text.chars() {
+            match character {
+                '&' => self.output.push_str("&amp;"),
+                '<' => self.output.push_str("&lt;"),
+                '>' => self.output.push_str("&gt;"),
+                _ => self.output.push(character),
+            }
+        }
+    }
+
+    /// Element containing only text
+    pub fn text_element(&mut self, name: &str, text: &str) {
+        self.start(name, &[]);
+        self.text(text);
+        self.end(name);
+    }
+
+    pub fn into_string(self) -> String {
+        self.output
+    }
+
+    fn open_tag(&mut self, name: &str, attributes: &[(&str, &str)]) {
+        self.output.push('<');
+        self.output.push_str(name);
+        for (key, value) in attributes {
+            self.output.push(' ');
+            self.output.push_str(key);
+            self.output.push_str("=\"");
+            self.escape_attribute(value);
+            self.output.push('"');
+        }
+    }
+
+    fn escape_attribute(&mut self, value: &str) {
+        for character in value.chars() {
+            match character {
+                '&' => self.output.push_str("&amp;"),
+                '<' => self.output.push_str("&lt;"),
+                '>' => self.output.push_str("&gt;"),
+                '"' => self.output.push_str("&quot;"),
+                '\'' => self.output.push_str("&apos;"),
+                _ => self.output.push(character),
+            }
+        }
+    }
+}
+
+/// Creates a parse error pointing to the line and column of `position` in `source`
+pub(crate) fn parse_error(source: &str, position: usize, message: impl ToString) -> TtsError {
+    let (line, column) = line_column(source, position);
+    TtsError::XmlParseError {
+        line,
+        column,
+        message: message.to_string(),
+    }
+}
+
+/// One based line and column of byte `position` in `source`
+pub(crate) fn line_column(source: &str, position: usize) -> (usize, usize) {
+    let position = position.min(source.len());
+    let mut line = 1;
+    let mut column = 1;
+    for (index, character) in source.char_indices() {
+        if index >= position {
+            break;
+        }
+        if character == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_text_and_attributes() {
+        let mut writer = XmlWriter::new();
+        writer.start("a", &[("title", "\"Tom\" & 'Jerry'")]);
+        writer.text("1 < 2 & 3 > 2");
+        writer.end("a");
+        assert_eq!(
+            "<a title=\"&quot;Tom&quot; &amp; &apos;Jerry&apos;\">1 &lt; 2 &amp; 3 &gt; 2</a>",
+            writer.into_string()
+        );
+    }
+
+    #[test]
+    fn empty_element() {
+        let mut writer = XmlWriter::new();
+        writer.empty("lexicon", &[("uri", "http://example.com/lexicon.xml")]);
+        assert_eq!(
+            "<lexicon uri=\"http://example.com/lexicon.xml\"/>",
+            writer.into_string()
+        );
+    }
+}